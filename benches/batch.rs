@@ -0,0 +1,42 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use takehome::builder::*;
+use takehome::circuit::Circuit;
+use takehome::field::Goldilocks;
+
+type F = Goldilocks;
+
+// A layered circuit of width inputs, where each level mixes every node 
+// with its neighbour, alternating multiplication and addition gates, 
+// followed by a hint squaring each node of the last level. 
+fn layered_circuit(width: usize, depth: usize) -> Circuit<F> {
+    let mut builder = Builder::<F>::new();
+    let mut layer = builder.batch_init(width);
+    for level in 0..depth {
+        layer = (0..width).map(|i| {
+            let (a, b) = (layer[i], layer[(i + 1) % width]);
+            if level % 2 == 0 { builder.mul(a, b) } else { builder.add(a, b) }
+        }).collect();
+    }
+    for node in layer {
+        builder.hint(&[node], |val: &[F]| Ok(val[0] * val[0]));
+    }
+    builder.finalize()
+}
+
+fn batch_benchmarks(c: &mut Criterion) {
+    let circuit = layered_circuit(64, 32);
+    let rows: Vec<Vec<F>> = (0..1000u64).map(|row| (0..64).map(|i| F::from(row * 64 + i)).collect()).collect();
+
+    let mut group = c.benchmark_group("batch_1000x64x32");
+    group.sample_size(10);
+    group.bench_function("evaluate_each", |b| {
+        b.iter(|| rows.iter().map(|row| circuit.evaluate(row).unwrap()).collect::<Vec<_>>())
+    });
+    group.bench_function("evaluate_batch", |b| {
+        b.iter(|| circuit.evaluate_batch(&rows))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, batch_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,77 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use takehome::builder::*;
+use takehome::field::{Field, Goldilocks};
+
+type F = Goldilocks;
+
+// A wide circuit: width inputs, followed by depth levels where each 
+// node mixes its neighbour from the previous level, alternating 
+// multiplication and addition gates. 
+fn wide_circuit(width: usize, depth: usize) -> Builder<F> {
+    let mut builder = Builder::<F>::new();
+    let inputs = builder.batch_init(width);
+    let values: Vec<F> = (0..width as u64).map(F::from).collect();
+    builder.batch_set(&inputs, &values);
+
+    let mut layer = inputs;
+    for level in 0..depth {
+        layer = (0..width).map(|i| {
+            let (a, b) = (layer[i], layer[(i + 1) % width]);
+            if level % 2 == 0 { builder.mul(a, b) } else { builder.add(a, b) }
+        }).collect();
+    }
+    builder
+}
+
+// A narrow deep circuit: a few independent chains of gates, so that 
+// every level holds only one gate per chain. 
+fn deep_circuit(chains: usize, length: usize) -> Builder<F> {
+    let mut builder = Builder::<F>::new();
+    let three = builder.constant(F::from(3));
+    for chain in 0..chains {
+        let mut node = builder.init();
+        builder.set(node, F::from(chain as u64));
+        for step in 0..length {
+            node = if step % 2 == 0 { builder.mul(node, three) } else { builder.add(node, three) };
+        }
+    }
+    builder
+}
+
+// Chains of hints where one chain is much slower than the others, 
+// so that under the level strategy it holds back every level. 
+fn skewed_circuit(chains: usize, length: usize, slow_work: u64) -> Builder<F> {
+    let mut builder = Builder::<F>::new();
+    for chain in 0..chains {
+        let work = if chain == 0 { slow_work } else { slow_work / 64 };
+        let mut node = builder.init();
+        builder.set(node, F::from(chain as u64 + 2));
+        for _ in 0..length {
+            node = builder.hint(std::slice::from_ref(&node), move |val: &[F]| {
+                Ok((0..work).fold(val[0], |acc, _| acc * acc + F::one()))
+            });
+        }
+    }
+    builder
+}
+
+fn compare_strategies(c: &mut Criterion, name: &str, builder: &mut Builder<F>) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    for strategy in [EvalStrategy::Level, EvalStrategy::Dataflow] {
+        builder.set_eval_strategy(strategy);
+        group.bench_function(BenchmarkId::from_parameter(format!("{:?}", strategy)), |b| {
+            b.iter(|| builder.fill_nodes().unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn scheduler_benchmarks(c: &mut Criterion) {
+    compare_strategies(c, "wide_4096x16", &mut wide_circuit(4096, 16));
+    compare_strategies(c, "deep_8x2000", &mut deep_circuit(8, 2000));
+    compare_strategies(c, "skewed_hints_16x200", &mut skewed_circuit(16, 200, 2000));
+}
+
+criterion_group!(benches, scheduler_benchmarks);
+criterion_main!(benches);
@@ -1,19 +1,44 @@
-use std::{cmp::max, fmt, sync::{Arc, RwLock}};
+use std::{cmp::max, collections::HashMap, fmt, iter, marker::PhantomData, ops::Range, sync::{atomic::{AtomicUsize, Ordering}, Arc}};
 use rayon::prelude::*;
 use log::debug;
-
-// Node is required to be wrapped in Arc for multiple thread access,
-// and to support user having pointers to node objects in circuit 
-type Node = Arc<RawNode>;
+use crate::{
+    circuit::{Circuit, Evaluator, ReverseIndex},
+    error::{BuilderError, EvalError, HintError}, 
+    field::Field, 
+    gate::Gate,
+    lookup::{LookupTable, TableId},
+    report::{ConstraintReport, PartialFill},
+    store::ValueStore,
+};
+
+// Source of the ids that tie nodes to the builder that created them
+static NEXT_BUILDER_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Handle to a node in a circuit, used to build gates on it and to read its value. 
+// Handles are plain indices into the builder, so they are Copy and cheap to pass around. 
+// id: the id of the node, which is its position in the circuit
+// builder_id: the builder that created the node, used to reject nodes of other builders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Node {
+    pub id: usize,
+    pub(crate) builder_id: usize,
+}
 
 // Keeps track of all gates at the level
 // Note that the gates are seperated by type
 // since otherwise some threads could take much longer than others to finish. 
-#[derive(Debug)]
-pub struct LevelGates {
-    adder_gates: Vec<AddGate>,
-    multiplier_gates: Vec<MultiplyGate>,
-    lambda_gates: Vec<LambdaGate>,
+#[derive(Debug, Default)]
+pub struct LevelGates<F: Field> {
+    pub(crate) adder_gates: Vec<AddGate<F>>,
+    pub(crate) multiplier_gates: Vec<MultiplyGate<F>>,
+    pub(crate) lambda_gates: Vec<LambdaGate<F>>,
+    pub(crate) subtractor_gates: Vec<SubGate<F>>,
+    pub(crate) negation_gates: Vec<NegGate<F>>,
+    pub(crate) add_const_gates: Vec<AddConstGate<F>>,
+    pub(crate) mul_const_gates: Vec<MulConstGate<F>>,
+    pub(crate) linear_gates: Vec<LinearGate<F>>,
+    pub(crate) custom_gates: Vec<CustomGate<F>>,
+    pub(crate) lookup_gates: Vec<LookupGate>,
 }
 
 // Struct to assert equality between the node with id 
@@ -22,84 +47,82 @@ pub struct LevelGates {
 // id's are assigned to nodes by builder as they are created. 
 #[derive(Debug)]
 pub struct EqualityAssertion {
-    left_id: usize,
-    right_id: usize,
+    pub(crate) left_id: usize,
+    pub(crate) right_id: usize,
 }
 
-// Struct that tracks the overall circuit.
-// nodes: a vector of all the nodes in the circuit 
-// gates: a vector of LevelGates. The ith element contains
-// a LevelGates structure containing all gates present at depth i.
-// assertions: a vector of equality assertions
+// Struct that tracks the overall circuit, computed over the field F.
+// circuit: the nodes, gates and assertions declared so far, 
+// handed out by finalize once the circuit is complete
+// values: the values of every node, in one contiguous buffer indexed by id
 // next_id: the next node added to the circuit will have this id. 
 // Every time a new node is added, this value will be incremented. 
-#[derive(Debug, Default)]
-pub struct Builder {
-    nodes: Vec<Node>, 
-    gates: Vec<LevelGates>,
-    assertions: Vec<EqualityAssertion>,
+// filled: whether every node holds the value computed from the inputs, 
+// apart from the nodes depending on changed_nodes
+// changed_nodes: the ids of the inputs set and the nodes overridden since the last fill
+// reverse_index: the children of every node, built by the first incremental 
+// fill and dropped whenever a node is added
+// shared_constants: the constant nodes created by shared_constant, by value
+#[derive(Debug)]
+pub struct Builder<F: Field> {
+    circuit: Circuit<F>,
+    values: ValueStore<F>,
     next_id: usize,
+    filled: bool,
+    changed_nodes: Vec<usize>,
+    reverse_index: Option<ReverseIndex>,
+    shared_constants: HashMap<F, Node>,
+}
+
+// Selects how fill_nodes schedules the gates of the circuit
+// Level: evaluate the gates level by level, waiting for every gate at 
+// a level to finish before starting the next one
+// Dataflow: evaluate each gate as soon as all of its inputs are filled, 
+// by counting the inputs each gate is still waiting on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvalStrategy {
+    #[default]
+    Level,
+    Dataflow,
+}
+
+// Selects how fill_and_check reacts to a failing assertion
+// Complete: evaluate the whole graph and report every assertion
+// FailFast: stop evaluating at the first failing assertion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckMode {
+    Complete,
+    FailFast,
 }
 
 // Used to track how each value in a node was computed, and mainly
 // for user to debug constraint failures in circuit. 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Derivation {
     Const,
     Input,
     Add,
     Mul,
     Hint,
+    Sub,
+    Neg,
+    AddConst,
+    MulConst,
+    Linear,
+    Custom,
+    Lookup,
 }
 
-// RawNode struct to track information in Node
-// value: A mutable pointer to the value 
-// (which is Option to handle unfilled values)
+// Information about a node that is fixed when the node is created
 // depth: the depth of the node (i.e. the level it is at)
-// id: the id of the node
-// parents: the id's of the nodes used to derive this nodes value
+// parents: the range of builder.parent_ids holding the ids of the 
+// nodes used to derive this nodes value
 // derivation: the method used to derive this nodes value 
-#[derive(Debug)]
-pub struct RawNode {
-    pub value: RwLock<Option<u32>>,
-    pub depth: u64,
-    pub id: usize,
-    pub parents: Vec<usize>, 
-    pub derivation: Derivation
-}
-
-impl RawNode {
-    /*
-        Allows value of a raw node to be set
-
-        ARGS:
-            value: value to set the node to 
-     */
-    fn set(&self, value: Option<u32>) {
-        *self.value.write().unwrap() = value; 
-    }
-
-    /*
-        Reads the value of a node
-
-        RETURNS: 
-            The value located at the AtomicPtr value field in RawNode
-     */
-    pub fn get(&self) -> u32 {
-        self.value.read().unwrap().unwrap_or_else(|| panic!("Value unfilled at node with id {:?}", self.id))
-    }
-}
-
-impl fmt::Display for RawNode {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.derivation {
-            Derivation::Const => write!(f, "Node {{ value: {}, depth: {}, id: {}, parents: {:?}, derivation: Constant }}", self.get(), self.depth, self.id, self.parents),
-            Derivation::Input => write!(f, "Node {{ value: {}, depth: {}, id: {}, parents: {:?}, derivation: Input }}", self.get(), self.depth, self.id, self.parents),
-            Derivation::Add => write!(f, "Node {{ value: {}, depth: {}, id: {}, parents: {:?}, derivation: Addition Gate }}", self.get(), self.depth, self.id, self.parents),
-            Derivation::Mul => write!(f, "Node {{ value: {}, depth: {}, id: {}, parents: {:?}, derivation: Multiplication Gate }}", self.get(), self.depth, self.id, self.parents),
-            Derivation::Hint => write!(f, "Node {{ value: {}, depth: {}, id: {}, parents: {:?}, derivation: Hint }}", self.get(), self.depth, self.id, self.parents),
-        }
-    }
+#[derive(Debug, Clone)]
+pub(crate) struct NodeInfo {
+    pub(crate) depth: u64,
+    pub(crate) parents: Range<usize>,
+    pub(crate) derivation: Derivation,
 }
 
 // AddGate structure, which has two input nodes and one output node. 
@@ -107,10 +130,11 @@ impl fmt::Display for RawNode {
 // and right_id is the position of the right node. 
 // output_id is the id of the output node containing the sum. 
 #[derive(Debug)]
-pub struct AddGate {
-    left_id: usize,
-    right_id: usize,
-    output_id: usize,
+pub struct AddGate<F: Field> {
+    pub(crate) left_id: usize,
+    pub(crate) right_id: usize,
+    pub(crate) output_id: usize,
+    marker: PhantomData<F>,
 }
 
 // MultiplyGate structure, which has two input nodes and one output node. 
@@ -118,27 +142,136 @@ pub struct AddGate {
 // and right_id is the position of the right node. 
 // output_id is the id of the output node containing the product. 
 #[derive(Debug)]
-pub struct MultiplyGate {
-    left_id: usize,
-    right_id: usize,
-    output_id: usize,
+pub struct MultiplyGate<F: Field> {
+    pub(crate) left_id: usize,
+    pub(crate) right_id: usize,
+    pub(crate) output_id: usize,
+    marker: PhantomData<F>,
+}
+
+// SubGate structure, which has two input nodes and one output node. 
+// left_id is the position of the left node in builder.nodes,
+// and right_id is the position of the right node. 
+// output_id is the id of the output node containing the difference left - right. 
+#[derive(Debug)]
+pub struct SubGate<F: Field> {
+    pub(crate) left_id: usize,
+    pub(crate) right_id: usize,
+    pub(crate) output_id: usize,
+    marker: PhantomData<F>,
+}
+
+// NegGate structure, which has one input node and one output node. 
+// input_id is the position of the input node in builder.nodes. 
+// output_id is the id of the output node containing the additive inverse of the input. 
+#[derive(Debug)]
+pub struct NegGate<F: Field> {
+    pub(crate) input_id: usize,
+    pub(crate) output_id: usize,
+    marker: PhantomData<F>,
+}
+
+// AddConstGate structure, which adds a fixed field element to one input node. 
+// input_id is the position of the input node in builder.nodes. 
+// constant is the field element added to the input, which is not a node. 
+// output_id is the id of the output node containing the sum. 
+#[derive(Debug)]
+pub struct AddConstGate<F: Field> {
+    pub(crate) input_id: usize,
+    pub(crate) constant: F,
+    pub(crate) output_id: usize,
+}
+
+// MulConstGate structure, which scales one input node by a fixed field element. 
+// input_id is the position of the input node in builder.nodes. 
+// constant is the field element the input is multiplied by, which is not a node. 
+// output_id is the id of the output node containing the product. 
+#[derive(Debug)]
+pub struct MulConstGate<F: Field> {
+    pub(crate) input_id: usize,
+    pub(crate) constant: F,
+    pub(crate) output_id: usize,
+}
+
+// LinearGate structure, which computes a weighted sum of any number of input nodes. 
+// terms holds the coefficient and the position in builder.nodes of each input node, 
+// where a node may appear in several terms. 
+// constant is the field element added to the sum, which is not a node. 
+// output_id is the id of the output node containing the linear combination. 
+#[derive(Debug)]
+pub struct LinearGate<F: Field> {
+    pub(crate) terms: Vec<(F, usize)>,
+    pub(crate) constant: F,
+    pub(crate) output_id: usize,
+}
+
+// LookupGate structure, which reads the value of a row of a lookup table. 
+// table: the position of the table in the circuit's tables
+// input_ids: the positions of the nodes forming the key of the row, in order
+// output_id: the id of the output node containing the value of the row
+#[derive(Debug)]
+pub struct LookupGate {
+    pub(crate) table: usize,
+    pub(crate) input_ids: Vec<usize>,
+    pub(crate) output_id: usize,
 }
 
-// Lambda type to use in order to specify a hint 
-pub type Lambda = fn(Vec<u32>) -> u32;
+// Lambda type used to store a hint. 
+// Any closure can be used, including ones that capture state such as 
+// lookup tables or keys. The closure is shared behind an Arc and is 
+// required to be Send + Sync since fill_nodes evaluates hints in parallel. 
+// A lambda returns one value per output node of its gate, so single
+// output hints are stored as lambdas returning a vector of length one. 
+// A lambda may fail on inputs it cannot handle by returning a HintError, 
+// which fill_nodes reports instead of panicking. 
+pub type Lambda<F> = Arc<dyn Fn(&[F]) -> Result<Vec<F>, HintError> + Send + Sync>;
 
 // LambdaGate structure to define arbitary hints based on other node values
 // input_ids: ids of input nodes to use 
-// output_id: id of the output node 
-// lambda: function used to determine the output.
-#[derive(Debug)]
-pub struct LambdaGate {
-    input_ids: Vec<usize>,
-    output_id: usize,
-    lambda: Lambda,
+// output_ids: ids of the output nodes, filled in order from the lambda's result 
+// lambda: function used to determine the outputs.
+pub struct LambdaGate<F: Field> {
+    pub(crate) input_ids: Vec<usize>,
+    pub(crate) output_ids: Vec<usize>,
+    pub(crate) lambda: Lambda<F>,
+}
+
+impl<F: Field> fmt::Debug for LambdaGate<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LambdaGate")
+            .field("input_ids", &self.input_ids)
+            .field("output_ids", &self.output_ids)
+            .finish_non_exhaustive()
+    }
+}
+
+// CustomGate structure holding a use of a user-defined gate
+// input_ids: ids of the input nodes, in the order passed to the gate
+// output_ids: ids of the output nodes, filled in order from the gate's result
+// gate: the gate, shared between all of its uses
+pub struct CustomGate<F: Field> {
+    pub(crate) input_ids: Vec<usize>,
+    pub(crate) output_ids: Vec<usize>,
+    pub(crate) gate: Arc<dyn Gate<F>>,
+}
+
+impl<F: Field> fmt::Debug for CustomGate<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CustomGate")
+            .field("name", &self.gate.name())
+            .field("input_ids", &self.input_ids)
+            .field("output_ids", &self.output_ids)
+            .finish()
+    }
+}
+
+impl<F: Field> Default for Builder<F> {
+    fn default() -> Self {
+        Builder::new()
+    }
 }
 
-impl Builder {
+impl<F: Field> Builder<F> {
     /*
         Creates a new empty circuit
 
@@ -146,7 +279,47 @@ impl Builder {
             An empty circuit with no nodes 
      */
     pub fn new() -> Self {
-        Builder::default()
+        Builder {
+            circuit: Circuit::new(NEXT_BUILDER_ID.fetch_add(1, Ordering::Relaxed)),
+            values: ValueStore::default(),
+            next_id: 0,
+            filled: false,
+            changed_nodes: Vec::new(),
+            reverse_index: None,
+            shared_constants: HashMap::new(),
+        }
+    }
+
+    /*
+        Completes the circuit, handing out an immutable Circuit that can be 
+        evaluated on many sets of inputs, concurrently if needed. Values set 
+        or computed in the builder are not carried over. 
+
+        RETURNS:
+            The circuit declared by this builder, which keeps its node ids, 
+            eval strategy and input order
+     */
+    pub fn finalize(self) -> Circuit<F> {
+        self.circuit
+    }
+
+    /*
+        Selects how fill_nodes schedules the gates of the circuit. 
+        Both strategies fill in the same values and report the same errors. 
+
+        ARGS:
+            strategy: EvalStrategy::Level (the default) or EvalStrategy::Dataflow
+     */
+    pub fn set_eval_strategy(&mut self, strategy: EvalStrategy) {
+        self.circuit.strategy = strategy;
+    }
+
+    /*
+        RETURNS:
+            The strategy fill_nodes uses to schedule the gates
+     */
+    pub fn eval_strategy(&self) -> EvalStrategy {
+        self.circuit.strategy
     }
     
     /*
@@ -156,17 +329,9 @@ impl Builder {
             An unfilled node object 
      */
     pub fn init(&mut self) -> Node {
-        let node = Arc::new(RawNode {
-            value: RwLock::new(None),
-            depth: 0,
-            id: self.next_id,
-            parents: Vec::new(),
-            derivation: Derivation::Input,
-        });
-        self.next_id += 1; 
-        self.nodes.push(node.clone());
-        node
-
+        let ids = self.push_nodes(1, 0, &[], Derivation::Input);
+        self.circuit.input_ids.push(ids.start);
+        self.handle(ids.start)
     }
 
     /*
@@ -179,18 +344,110 @@ impl Builder {
             A vector of input nodes to use for the circuit  
      */
     pub fn batch_init(&mut self, num_inputs: usize) -> Vec<Node> {
-        let init_count = self.next_id; 
-        let vector_input: Vec<Node> = (0..num_inputs).into_par_iter().map(|i| {
-            Arc::new(RawNode {
-                value: RwLock::new(None),
-                depth: 0,
-                id: init_count + i,
-                parents: Vec::new(),
-                derivation: Derivation::Input,
-            })}).collect();
-        self.nodes.extend(vector_input.clone());
-        self.next_id += num_inputs;
-        vector_input
+        let ids = self.push_nodes(num_inputs, 0, &[], Derivation::Input);
+        self.circuit.input_ids.extend(ids.clone());
+        ids.map(|id| self.handle(id)).collect()
+    }
+
+    /*
+        Appends nodes sharing the same depth, parents and derivation 
+        to the circuit, with unfilled values
+
+        ARGS:
+            count: the number of nodes to add
+            depth: the depth of the new nodes
+            parents: the ids of the nodes used to derive the new nodes
+            derivation: the method used to derive the new nodes
+
+        RETURNS:
+            The range of ids of the new nodes
+     */
+    fn push_nodes(&mut self, count: usize, depth: u64, parents: &[usize], derivation: Derivation) -> Range<usize> {
+        self.filled = false;
+        self.reverse_index = None;
+
+        let parent_start = self.circuit.parent_ids.len();
+        self.circuit.parent_ids.extend_from_slice(parents);
+        let info = NodeInfo { depth, parents: parent_start..self.circuit.parent_ids.len(), derivation };
+
+        self.circuit.nodes.extend(iter::repeat_n(info, count));
+        self.values.extend(count);
+        let ids = self.next_id..self.next_id + count;
+        self.next_id += count;
+        ids
+    }
+
+    /*
+        RETURNS:
+            A handle to the node with the given id of this builder
+     */
+    fn handle(&self, id: usize) -> Node {
+        self.circuit.handle(id)
+    }
+
+    /*
+        Reads the value of a node
+
+        RETURNS: 
+            The value of the node, panicking if it is unfilled
+     */
+    pub fn get(&self, node: Node) -> F {
+        self.try_get(node).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /*
+        Reads the value of a node without panicking
+
+        RETURNS: 
+            The value of the node, BuilderError::ForeignNode if it belongs to 
+            another builder, or BuilderError::UnfilledNode if it has not been 
+            set or computed yet
+     */
+    pub fn try_get(&self, node: Node) -> Result<F, BuilderError> {
+        self.check_owned(node)?;
+        self.values.get(node.id).ok_or(BuilderError::UnfilledNode { id: node.id })
+    }
+
+    /*
+        RETURNS:
+            The depth of the node (i.e. the level it is at)
+     */
+    pub fn depth(&self, node: Node) -> u64 {
+        self.circuit.depth(node)
+    }
+
+    /*
+        RETURNS:
+            The ids of the nodes used to derive the node's value
+     */
+    pub fn parents(&self, node: Node) -> &[usize] {
+        self.circuit.parents(node)
+    }
+
+    /*
+        RETURNS:
+            The method used to derive the node's value
+     */
+    pub fn derivation(&self, node: Node) -> Derivation {
+        self.circuit.derivation(node)
+    }
+
+    /*
+        Looks up a node by id, for example to follow the ids returned by parents
+
+        RETURNS:
+            A handle to the node with the given id, or None if the builder has no such node
+     */
+    pub fn node(&self, id: usize) -> Option<Node> {
+        (id < self.circuit.nodes.len()).then(|| self.handle(id))
+    }
+
+    /*
+        RETURNS:
+            The builder's circuit together with its values, for evaluation
+     */
+    fn evaluator(&self) -> Evaluator<'_, F> {
+        Evaluator { circuit: &self.circuit, values: &self.values }
     }
 
     /*
@@ -202,32 +459,147 @@ impl Builder {
             node: the node to change the value of
             value: the new value node should hold  
      */
-    pub fn set(&mut self, node: Node, value: u32) {
-        if node.depth == 0 && node.derivation != Derivation::Const {
-            node.set(Some(value));
-        } else {
-            debug!("Cannot set value of non-input node {} as it is derived.", node)
+    pub fn set(&mut self, node: Node, value: F) {
+        if let Err(error) = self.try_set(node, value) {
+            debug!("{}", error)
         }
     }
 
+    /*
+        Sets the value of an input node in the graph. 
+
+        ARGS:
+            node: the node to change the value of
+            value: the new value node should hold  
+
+        RETURNS:
+            BuilderError::DerivedNode if the node is not an input node, 
+            or BuilderError::ForeignNode if it belongs to another builder
+     */
+    pub fn try_set(&mut self, node: Node, value: F) -> Result<(), BuilderError> {
+        self.check_settable(node)?;
+        self.values.set(node.id, Some(value));
+        self.changed_nodes.push(node.id);
+        Ok(())
+    }
+
     /*
         Sets the value of a a vector of nodes in the graph. Does not allow 
         setting the value of a node that is driven by other nodes 
-        (as the output of a hint, or an arithmetic gate).
+        (as the output of a hint, or an arithmetic gate). Each node that cannot 
+        be set is logged like in set, while the other nodes are still set. 
+        Panics with BuilderError::LengthMismatch if the slices differ in length, 
+        which try_batch_set returns instead. 
 
         ARGS:
             nodes: the vector of nodes to change the value of
             values: the new values node should hold  
      */
-    pub fn batch_set(&mut self, nodes: &[Node], values: &[u32]) {
-        assert_eq!(nodes.len(), values.len());
-        nodes.par_iter().enumerate().for_each(|(i, node)| {
-            if node.depth == 0 && node.derivation != Derivation::Const {
-                node.set(Some(values[i]));
-            } else {
-                debug!("Cannot set value of non-input node {:?} as it is derived.", node)
+    pub fn batch_set(&mut self, nodes: &[Node], values: &[F]) {
+        if nodes.len() != values.len() {
+            panic!("{}", BuilderError::LengthMismatch { left: nodes.len(), right: values.len() });
+        }
+        for (&node, &value) in nodes.iter().zip(values) {
+            if let Err(error) = self.try_set(node, value) {
+                debug!("{}", error);
             }
-        });        
+        }
+    }
+
+    /*
+        Sets the value of a vector of input nodes in the graph. Either every 
+        node is set or, if any of them cannot be set, none of them are. 
+
+        ARGS:
+            nodes: the vector of nodes to change the value of
+            values: the new values node should hold  
+
+        RETURNS:
+            BuilderError::LengthMismatch if the slices differ in length, 
+            or the error for the first node that cannot be set
+     */
+    pub fn try_batch_set(&mut self, nodes: &[Node], values: &[F]) -> Result<(), BuilderError> {
+        if nodes.len() != values.len() {
+            return Err(BuilderError::LengthMismatch { left: nodes.len(), right: values.len() });
+        }
+        nodes.iter().try_for_each(|&node| self.check_settable(node))?;
+        for (&node, &value) in nodes.iter().zip(values) {
+            self.values.set(node.id, Some(value));
+            self.changed_nodes.push(node.id);
+        }
+        Ok(())
+    }
+
+    /*
+        Overwrites the value of the output of a hint, custom gate or lookup, the 
+        nodes whose value a prover chooses. This is meant for testing the soundness 
+        of a circuit: overriding a hint output plays the part of a malicious prover 
+        choosing its own witness, which the assertions on the hint should then reject. 
+        The override is treated like a newly set input by fill_nodes_incremental, 
+        which recomputes the nodes derived from it, while fill_nodes re-evaluates 
+        the hint, gate or lookup and so discards the override. Inputs are set with 
+        set, and constants and the outputs of arithmetic gates cannot be overridden, 
+        since no fill would restore them. 
+
+        ARGS:
+            node: the node to change the value of
+            value: the new value node should hold
+
+        RETURNS:
+            BuilderError::ForeignNode if the node belongs to another builder, or 
+            BuilderError::NotOverridable if it is not a hint, custom gate or lookup output
+     */
+    pub fn override_value(&mut self, node: Node, value: F) -> Result<(), BuilderError> {
+        self.check_owned(node)?;
+        match self.circuit.nodes[node.id].derivation {
+            Derivation::Hint | Derivation::Custom | Derivation::Lookup => {}
+            derivation => return Err(BuilderError::NotOverridable { id: node.id, derivation }),
+        }
+        self.values.set(node.id, Some(value));
+        self.changed_nodes.push(node.id);
+        Ok(())
+    }
+
+    /*
+        Checks that a node was created by this builder
+
+        RETURNS:
+            BuilderError::ForeignNode if the node belongs to another builder
+     */
+    pub(crate) fn check_owned(&self, node: Node) -> Result<(), BuilderError> {
+        if node.builder_id == self.circuit.builder_id && node.id < self.circuit.nodes.len() {
+            Ok(())
+        } else {
+            Err(BuilderError::ForeignNode { id: node.id })
+        }
+    }
+
+    /*
+        Checks that a node was created by this builder, for the gate 
+        constructors that have no try_ variant
+
+        RETURNS:
+            The id of the node, panicking with BuilderError::ForeignNode 
+            if the node belongs to another builder
+     */
+    fn owned_id(&self, node: Node) -> usize {
+        self.check_owned(node).unwrap_or_else(|error| panic!("{}", error));
+        node.id
+    }
+
+    /*
+        Checks that a node is an input node of this builder, 
+        so that its value may be set
+
+        RETURNS:
+            BuilderError::ForeignNode or BuilderError::DerivedNode otherwise
+     */
+    fn check_settable(&self, node: Node) -> Result<(), BuilderError> {
+        self.check_owned(node)?;
+        match self.circuit.nodes[node.id].derivation {
+            Derivation::Input => Ok(()),
+            derivation => Err(BuilderError::DerivedNode { id: node.id, derivation }),
+        }
     }
     
     /*
@@ -239,16 +611,30 @@ impl Builder {
         RETURNS:
             A constant node containing value 
      */
-    pub fn constant(&mut self, value: u32) -> Node {
-        let node = Arc::new(RawNode {
-            value: RwLock::new(Some(value)),
-            depth: 0,
-            id: self.next_id,
-            parents: Vec::new(),
-            derivation: Derivation::Const,
-        });
-        self.next_id += 1; 
-        self.nodes.push(node.clone());
+    pub fn constant(&mut self, value: F) -> Node {
+        let ids = self.push_nodes(1, 0, &[], Derivation::Const);
+        self.circuit.constants.push((ids.start, value));
+        self.values.set(ids.start, Some(value));
+        self.handle(ids.start)
+    }
+
+    /*
+        Returns a constant node holding a value, creating it on the first call 
+        with that value only, so that gadgets asserting against constants such 
+        as zero or one do not add a constant node per call
+
+        ARGS:
+            value: the constant value the node should hold
+
+        RETURNS:
+            The constant node shared by every call with this value
+     */
+    pub(crate) fn shared_constant(&mut self, value: F) -> Node {
+        if let Some(&node) = self.shared_constants.get(&value) {
+            return node;
+        }
+        let node = self.constant(value);
+        self.shared_constants.insert(value, node);
         node
     }
 
@@ -261,19 +647,13 @@ impl Builder {
         RETURNS:
             A vector of constant nodes 
      */
-    pub fn batch_constant(&mut self, values: &[u32]) -> Vec<Node> {
-        let init_count = self.next_id; 
-        let vector_constant: Vec<Node> = (0..values.len()).into_par_iter().map(|i| {
-            Arc::new(RawNode {
-                value: RwLock::new(Some(values[i])),
-                depth: 0,
-                id: init_count + i,
-                parents: Vec::new(),
-                derivation: Derivation::Const,
-            })}).collect();
-        self.nodes.extend(vector_constant.clone());
-        self.next_id += values.len();
-        vector_constant
+    pub fn batch_constant(&mut self, values: &[F]) -> Vec<Node> {
+        let ids = self.push_nodes(values.len(), 0, &[], Derivation::Const);
+        for (id, &value) in ids.clone().zip(values) {
+            self.circuit.constants.push((id, value));
+            self.values.set(id, Some(value));
+        }
+        ids.map(|id| self.handle(id)).collect()
     }
     
     /*
@@ -288,38 +668,22 @@ impl Builder {
             A node holding the formal sum of node a and node b  
      */
     pub fn add(&mut self, a: Node, b: Node) -> Node {
-        let a_depth = a.depth;
-        let b_depth = b.depth;
+        let a_depth = self.circuit.nodes[self.owned_id(a)].depth;
+        let b_depth = self.circuit.nodes[self.owned_id(b)].depth;
 
         let depth_gate = max(a_depth, b_depth);
 
-        let output_node = Arc::new(RawNode {
-            value: RwLock::new(None),
-            depth: depth_gate + 1,
-            id: self.next_id,
-            parents: vec![a.id, b.id],
-            derivation: Derivation::Add
-        });
+        let output_id = self.push_nodes(1, depth_gate + 1, &[a.id, b.id], Derivation::Add).start;
         
         let add_gate = AddGate {
             left_id: a.id,
             right_id: b.id,
-            output_id: output_node.id,
+            output_id,
+            marker: PhantomData,
         };
 
-        self.nodes.push(output_node.clone());
-        self.next_id += 1; 
-
-        if self.gates.len() <= depth_gate as usize {
-            self.gates.push(LevelGates {
-                adder_gates: Vec::new(),
-                multiplier_gates: Vec::new(),
-                lambda_gates: Vec::new(),
-            });
-        }
-
-        self.gates[depth_gate as usize].adder_gates.push(add_gate);
-        output_node
+        self.level_gates(depth_gate).adder_gates.push(add_gate);
+        self.handle(output_id)
     }
     
     /*
@@ -334,38 +698,265 @@ impl Builder {
             A node holding the formal product of node a and node b  
      */
     pub fn mul(&mut self, a: Node, b: Node) -> Node {
-        let a_depth = a.depth;
-        let b_depth = b.depth;
+        let a_depth = self.circuit.nodes[self.owned_id(a)].depth;
+        let b_depth = self.circuit.nodes[self.owned_id(b)].depth;
 
         let depth_gate = max(a_depth, b_depth);
 
-        let output_node = Arc::new(RawNode {
-            value: RwLock::new(None),
-            depth: depth_gate + 1,
-            id: self.next_id,
-            parents: vec![a.id, b.id],
-            derivation: Derivation::Mul
-        });
-
+        let output_id = self.push_nodes(1, depth_gate + 1, &[a.id, b.id], Derivation::Mul).start;
+        
         let multiply_gate = MultiplyGate {
             left_id: a.id,
             right_id: b.id,
-            output_id: output_node.id,
+            output_id,
+            marker: PhantomData,
+        };
+
+        self.level_gates(depth_gate).multiplier_gates.push(multiply_gate);
+        self.handle(output_id)
+    }
+    
+    /*
+        Initializes a new node that is the output of a subtraction gate
+        taking in two already existing nodes in the graph. 
+
+        ARGS:
+            a: the node subtracted from
+            b: the node subtracted
+
+        RETURNS:
+            A node holding the formal difference a - b, which wraps 
+            around the field modulus when b is larger than a
+     */
+    pub fn sub(&mut self, a: Node, b: Node) -> Node {
+        let depth_gate = max(self.circuit.nodes[self.owned_id(a)].depth, self.circuit.nodes[self.owned_id(b)].depth);
+        let output_id = self.push_nodes(1, depth_gate + 1, &[a.id, b.id], Derivation::Sub).start;
+
+        let sub_gate = SubGate {
+            left_id: a.id,
+            right_id: b.id,
+            output_id,
+            marker: PhantomData,
+        };
+
+        self.level_gates(depth_gate).subtractor_gates.push(sub_gate);
+        self.handle(output_id)
+    }
+
+    /*
+        Initializes a new node that is the output of a negation gate
+        taking in an already existing node in the graph. 
+
+        ARGS:
+            a: the input to the negation gate
+
+        RETURNS:
+            A node holding the additive inverse of node a
+     */
+    pub fn neg(&mut self, a: Node) -> Node {
+        let depth_gate = self.circuit.nodes[self.owned_id(a)].depth;
+        let output_id = self.push_nodes(1, depth_gate + 1, &[a.id], Derivation::Neg).start;
+
+        let neg_gate = NegGate {
+            input_id: a.id,
+            output_id,
+            marker: PhantomData,
+        };
+
+        self.level_gates(depth_gate).negation_gates.push(neg_gate);
+        self.handle(output_id)
+    }
+
+    /*
+        Initializes a new node holding the sum of an existing node and a 
+        field element. Unlike adding a node created by constant, no node 
+        is created for the field element. 
+
+        ARGS:
+            a: the input to the gate
+            constant: the field element added to a
+
+        RETURNS:
+            A node holding the formal sum of node a and the constant
+     */
+    pub fn add_const(&mut self, a: Node, constant: F) -> Node {
+        let depth_gate = self.circuit.nodes[self.owned_id(a)].depth;
+        let output_id = self.push_nodes(1, depth_gate + 1, &[a.id], Derivation::AddConst).start;
+
+        let add_const_gate = AddConstGate {
+            input_id: a.id,
+            constant,
+            output_id,
         };
 
-        self.nodes.push(output_node.clone());
-        self.next_id += 1; 
+        self.level_gates(depth_gate).add_const_gates.push(add_const_gate);
+        self.handle(output_id)
+    }
+
+    /*
+        Initializes a new node holding the product of an existing node and a 
+        field element. Unlike multiplying by a node created by constant, no 
+        node is created for the field element. 
+
+        ARGS:
+            a: the input to the gate
+            constant: the field element a is multiplied by
+
+        RETURNS:
+            A node holding the formal product of node a and the constant
+     */
+    pub fn mul_const(&mut self, a: Node, constant: F) -> Node {
+        let depth_gate = self.circuit.nodes[self.owned_id(a)].depth;
+        let output_id = self.push_nodes(1, depth_gate + 1, &[a.id], Derivation::MulConst).start;
+
+        let mul_const_gate = MulConstGate {
+            input_id: a.id,
+            constant,
+            output_id,
+        };
+
+        self.level_gates(depth_gate).mul_const_gates.push(mul_const_gate);
+        self.handle(output_id)
+    }
+
+    /*
+        Initializes a new node holding a linear combination of existing nodes, 
+        computed by a single gate. A sum of n nodes built from add calls takes 
+        n - 1 gates and up to n - 1 levels, while this takes one gate at the 
+        level after its deepest input. 
+
+        ARGS:
+            terms: the coefficient and node of each term of the sum
+            constant: the field element added to the sum
+
+        RETURNS:
+            A node holding constant + c_1 * a_1 + ... + c_n * a_n, for the 
+            terms (c_i, a_i). With no terms the node holds the constant. 
+     */
+    pub fn linear_combination(&mut self, terms: &[(F, Node)], constant: F) -> Node {
+        let depth_gate = terms.iter().map(|&(_, node)| self.circuit.nodes[self.owned_id(node)].depth).max().unwrap_or(0);
+        let input_ids: Vec<usize> = terms.iter().map(|(_, node)| node.id).collect();
+        let output_id = self.push_nodes(1, depth_gate + 1, &input_ids, Derivation::Linear).start;
+
+        let linear_gate = LinearGate {
+            terms: terms.iter().map(|&(coefficient, node)| (coefficient, node.id)).collect(),
+            constant,
+            output_id,
+        };
+
+        self.level_gates(depth_gate).linear_gates.push(linear_gate);
+        self.handle(output_id)
+    }
 
-        if self.gates.len() <= depth_gate as usize {
-            self.gates.push(LevelGates {
-                adder_gates: Vec::new(),
-                multiplier_gates: Vec::new(),
-                lambda_gates: Vec::new(),
+    /*
+        Registers a lookup table with the builder. The table is stored once, 
+        and shared by every lookup gate built on it. 
+
+        ARGS:
+            table: the rows of the table
+
+        RETURNS:
+            A handle to the table, to pass to lookup
+     */
+    pub fn register_table(&mut self, table: LookupTable<F>) -> TableId {
+        self.circuit.tables.push(table);
+        TableId { index: self.circuit.tables.len() - 1, builder_id: self.circuit.builder_id }
+    }
+
+    /*
+        RETURNS:
+            The table registered with the given handle. Panics if the 
+            table was not registered with this builder. 
+     */
+    pub fn table(&self, table: TableId) -> &LookupTable<F> {
+        self.try_table(table).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /*
+        RETURNS:
+            The table registered with the given handle, or BuilderError::UnknownTable 
+            if the table was not registered with this builder
+     */
+    pub fn try_table(&self, table: TableId) -> Result<&LookupTable<F>, BuilderError> {
+        if table.builder_id != self.circuit.builder_id || table.index >= self.circuit.tables.len() {
+            return Err(BuilderError::UnknownTable { index: table.index });
+        }
+        Ok(&self.circuit.tables[table.index])
+    }
+
+    /*
+        Initializes a new node holding the value of the row of a table keyed by 
+        existing nodes. fill_nodes fails if the table has no such row, and 
+        check_constraints only accepts the output if the inputs and output form 
+        a row of the table. 
+
+        ARGS:
+            table: a table registered with this builder
+            inputs: the nodes forming the key, as many as the arity of the table
+
+        RETURNS:
+            A node holding the value of the row keyed by the inputs
+     */
+    pub fn lookup(&mut self, table: TableId, inputs: &[Node]) -> Node {
+        self.try_lookup(table, inputs).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /*
+        Initializes a new node holding the value of the row of a table keyed by 
+        existing nodes, without panicking on invalid arguments
+
+        ARGS:
+            table: a table registered with this builder
+            inputs: the nodes forming the key, as many as the arity of the table
+
+        RETURNS:
+            The output node of the lookup gate, BuilderError::UnknownTable if the 
+            table was not registered with this builder, BuilderError::ArityMismatch 
+            if the number of inputs differs from the arity of the table, 
+            BuilderError::EmptyArguments if the table takes no inputs, or 
+            BuilderError::ForeignNode if an input belongs to another builder
+     */
+    pub fn try_lookup(&mut self, table: TableId, inputs: &[Node]) -> Result<Node, BuilderError> {
+        let arity = self.try_table(table)?.arity();
+        if inputs.len() != arity {
+            return Err(BuilderError::ArityMismatch { 
+                gate: format!("lookup into table {}", table.index), 
+                expected: arity, 
+                given: inputs.len(),
             });
         }
+        inputs.iter().try_for_each(|&input| self.check_owned(input))?;
+
+        let depth_gate = inputs.iter().map(|input| self.circuit.nodes[input.id].depth).max().ok_or(BuilderError::EmptyArguments)?;
+        let input_ids: Vec<_> = inputs.iter().map(|node| node.id).collect();
+        let output_id = self.push_nodes(1, depth_gate + 1, &input_ids, Derivation::Lookup).start;
+
+        let lookup_gate = LookupGate {
+            table: table.index,
+            input_ids,
+            output_id,
+        };
+
+        self.level_gates(depth_gate).lookup_gates.push(lookup_gate);
+        Ok(self.handle(output_id))
+    }
+
+    /*
+        Gives access to the gates evaluated at a level, creating 
+        the levels up to it if needed
 
-        self.gates[depth_gate as usize].multiplier_gates.push(multiply_gate);
-        output_node
+        ARGS:
+            depth: the level of the gates, which is the depth of their deepest input
+
+        RETURNS:
+            The gates at the level
+     */
+    fn level_gates(&mut self, depth: u64) -> &mut LevelGates<F> {
+        let depth = depth as usize;
+        if self.circuit.gates.len() <= depth {
+            self.circuit.gates.resize_with(depth + 1, LevelGates::default);
+        }
+        &mut self.circuit.gates[depth]
     }
     
     /*
@@ -373,47 +964,151 @@ impl Builder {
 
         ARGS:
             arguments: an array of nodes that serve as inputs to the lambda
-            lambda: a function that relates the values of these nodes to the value of the output (which is returned)
+            lambda: a function or closure that relates the values of these nodes to the value of the output (which is returned)
 
         RETURNS:
             Returns a node corresponding to the output of the lambda gate that is just in time filled once the arguments are computed. 
      */
-    pub fn hint(&mut self, arguments: &[Node], lambda: Lambda) -> Node {
+    pub fn hint<L>(&mut self, arguments: &[Node], lambda: L) -> Node 
+    where
+        L: Fn(&[F]) -> Result<F, HintError> + Send + Sync + 'static,
+    {
+        self.try_hint(arguments, lambda).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /*
+        Allows for a hint to be given, without panicking on invalid arguments
+
+        ARGS:
+            arguments: an array of nodes that serve as inputs to the lambda
+            lambda: a function or closure that relates the values of these nodes to the value of the output
+
+        RETURNS:
+            The output node of the lambda gate, BuilderError::EmptyArguments if no 
+            arguments are given, or BuilderError::ForeignNode if an argument belongs 
+            to another builder
+     */
+    pub fn try_hint<L>(&mut self, arguments: &[Node], lambda: L) -> Result<Node, BuilderError> 
+    where
+        L: Fn(&[F]) -> Result<F, HintError> + Send + Sync + 'static,
+    {
+        let mut outputs = self.try_hint_many(arguments, 1, move |values: &[F]| Ok(vec![lambda(values)?]))?;
+        Ok(outputs.pop().unwrap())
+    }
+
+    /*
+        Allows for a hint with several outputs to be given (useful for bit 
+        decompositions, quotient/remainder pairs or limb splits, which are 
+        then computed by a single call of the lambda)
+
+        ARGS:
+            arguments: an array of nodes that serve as inputs to the lambda
+            num_outputs: the number of values the lambda returns
+            lambda: a function or closure that relates the values of these nodes to the values of the outputs
+
+        RETURNS:
+            Returns num_outputs nodes, where the ith node holds the ith value returned by the lambda. 
+            Every output node records the arguments as its parents. 
+     */
+    pub fn hint_many<L>(&mut self, arguments: &[Node], num_outputs: usize, lambda: L) -> Vec<Node> 
+    where
+        L: Fn(&[F]) -> Result<Vec<F>, HintError> + Send + Sync + 'static,
+    {
+        self.try_hint_many(arguments, num_outputs, lambda).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /*
+        Allows for a hint with several outputs to be given, without panicking on invalid arguments
+
+        ARGS:
+            arguments: an array of nodes that serve as inputs to the lambda
+            num_outputs: the number of values the lambda returns
+            lambda: a function or closure that relates the values of these nodes to the values of the outputs
+
+        RETURNS:
+            The output nodes of the lambda gate, BuilderError::EmptyArguments if no 
+            arguments are given, or BuilderError::ForeignNode if an argument belongs 
+            to another builder
+     */
+    pub fn try_hint_many<L>(&mut self, arguments: &[Node], num_outputs: usize, lambda: L) -> Result<Vec<Node>, BuilderError> 
+    where
+        L: Fn(&[F]) -> Result<Vec<F>, HintError> + Send + Sync + 'static,
+    {
+        arguments.iter().try_for_each(|&arg| self.check_owned(arg))?;
+
         // read in arguments which should be other nodes in the graph
-        let depth_gate = arguments.iter().map(|arg| arg.depth).max().unwrap();
-
-        // create an output node to store the value in
-        let output_node = Arc::new(RawNode {
-            value: RwLock::new(None),
-            depth: depth_gate + 1,
-            id: self.next_id,
-            parents: arguments.iter().map(|arg| arg.id).collect(),
-            derivation: Derivation::Hint
-        });
-        
-        // get the positions of the nodes in the vector self.nodes, 
+        let depth_gate = arguments.iter().map(|arg| self.circuit.nodes[arg.id].depth).max().ok_or(BuilderError::EmptyArguments)?;
+
+        // get the positions of the nodes in the vector self.circuit.nodes, 
         // so that the values can be extracted later
         let argument_ids: Vec<_> = arguments.iter().map(|node| node.id).collect();
 
+        // create output nodes to store the values in
+        let output_ids = self.push_nodes(num_outputs, depth_gate + 1, &argument_ids, Derivation::Hint);
+
         let lambda_gate = LambdaGate {
             input_ids: argument_ids,
-            output_id: output_node.id,
-            lambda,
+            output_ids: output_ids.clone().collect(),
+            lambda: Arc::new(lambda),
         };
 
-        self.nodes.push(output_node.clone());
-        self.next_id += 1; 
+        self.level_gates(depth_gate).lambda_gates.push(lambda_gate);
+        Ok(output_ids.map(|id| self.handle(id)).collect())
+    }
+    
+    /*
+        Uses a user-defined gate on existing nodes, declaring the constraints 
+        of the gate between them and its outputs
 
-        if self.gates.len() <= depth_gate as usize {
-            self.gates.push(LevelGates {
-                adder_gates: Vec::new(),
-                multiplier_gates: Vec::new(),
-                lambda_gates: Vec::new(),
+        ARGS:
+            gate: the gate, which may be shared between several uses
+            inputs: the input nodes of the gate, as many as its arity
+
+        RETURNS:
+            The output nodes of the gate, as many as its num_outputs. 
+            Every output node records the inputs as its parents. 
+     */
+    pub fn custom_gate(&mut self, gate: Arc<dyn Gate<F>>, inputs: &[Node]) -> Vec<Node> {
+        self.try_custom_gate(gate, inputs).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /*
+        Uses a user-defined gate on existing nodes, without panicking on invalid inputs
+
+        ARGS:
+            gate: the gate, which may be shared between several uses
+            inputs: the input nodes of the gate, as many as its arity
+
+        RETURNS:
+            The output nodes of the gate, BuilderError::ArityMismatch if the number 
+            of inputs differs from the arity of the gate, BuilderError::EmptyArguments 
+            if the gate takes no inputs, or BuilderError::ForeignNode if an input 
+            belongs to another builder
+     */
+    pub fn try_custom_gate(&mut self, gate: Arc<dyn Gate<F>>, inputs: &[Node]) -> Result<Vec<Node>, BuilderError> {
+        if inputs.len() != gate.arity() {
+            return Err(BuilderError::ArityMismatch { 
+                gate: gate.name().to_string(), 
+                expected: gate.arity(), 
+                given: inputs.len(),
             });
         }
+        inputs.iter().try_for_each(|&input| self.check_owned(input))?;
+
+        let depth_gate = inputs.iter().map(|input| self.circuit.nodes[input.id].depth).max().ok_or(BuilderError::EmptyArguments)?;
+        let input_ids: Vec<_> = inputs.iter().map(|node| node.id).collect();
+        let output_ids = self.push_nodes(gate.num_outputs(), depth_gate + 1, &input_ids, Derivation::Custom);
+
+        let custom_gate = CustomGate {
+            input_ids,
+            output_ids: output_ids.clone().collect(),
+            gate: gate.clone(),
+        };
 
-        self.gates[depth_gate as usize].lambda_gates.push(lambda_gate);
-        output_node
+        self.level_gates(depth_gate).custom_gates.push(custom_gate);
+        let outputs: Vec<Node> = output_ids.map(|id| self.handle(id)).collect();
+        gate.constraints(self, inputs, &outputs);
+        Ok(outputs)
     }
     
     /*
@@ -429,7 +1124,25 @@ impl Builder {
             left_id: left_arg.id,
             right_id: right_arg.id,
         };
-        self.assertions.push(assertion);
+        self.circuit.assertions.push(assertion);
+    }
+
+    /*
+        Allows for a single assertion to be declared, checking that both 
+        nodes belong to this builder. 
+
+        ARGS:
+            left_arg: the left inputs
+            right_arg: the right inputs
+
+        RETURNS:
+            BuilderError::ForeignNode if either node belongs to another builder
+     */
+    pub fn try_assert_equal(&mut self, left_arg: Node, right_arg: Node) -> Result<(), BuilderError> {
+        self.check_owned(left_arg)?;
+        self.check_owned(right_arg)?;
+        self.assert_equal(left_arg, right_arg);
+        Ok(())
     }
 
     /*
@@ -449,85 +1162,175 @@ impl Builder {
                 left_id: left_args[i].id,
                 right_id: right_args[i].id,
             }}).collect();
-        self.assertions.extend(new_assertions);
+        self.circuit.assertions.extend(new_assertions);
+    }
+
+    /*
+        Allows for a batch of assertions to be declared, checking the 
+        lengths of the slices and that every node belongs to this builder. 
+        No assertion is added unless all of them are valid. 
+
+        ARGS:
+            left_args: the vector of left inputs
+            right_arg: the vector of right inputs
+
+        RETURNS:
+            BuilderError::LengthMismatch if the slices differ in length, 
+            or BuilderError::ForeignNode for the first node from another builder
+     */
+    pub fn try_batch_assert_equal(&mut self, left_args: &[Node], right_args: &[Node]) -> Result<(), BuilderError> {
+        if left_args.len() != right_args.len() {
+            return Err(BuilderError::LengthMismatch { left: left_args.len(), right: right_args.len() });
+        }
+        left_args.iter().chain(right_args).try_for_each(|&node| self.check_owned(node))?;
+        self.batch_assert_equal(left_args, right_args);
+        Ok(())
     }
 
     /*
         Multithreaded function to fill in all the nodes of the graph given inputs. 
-        Expects that all inputs have already been set. If it encounters an unfilled 
-        node in the graph, it throws an error message. 
-     */
-    pub fn fill_nodes(&mut self) {   
-        for level_gate in &self.gates {
-            let add_gates = &level_gate.adder_gates;
-            let multiply_gates = &level_gate.multiplier_gates; 
-            let lambda_gates = &level_gate.lambda_gates; 
-
-            // iterate over all the gates, read the inputs and drive the outputs accordingly. 
-            add_gates.par_iter().for_each(|gate| {
-                let left_value = self.nodes[gate.left_id].get();
-                let right_value = self.nodes[gate.right_id].get();
-                self.nodes[gate.output_id].set(Some(left_value + right_value));
-            });
+        Expects that all inputs have already been set. If a gate reads an unfilled 
+        node, typically an input that was never set, EvalError::UnfilledNode is returned. 
 
-            multiply_gates.par_iter().for_each(|gate| {
-                let left_value = self.nodes[gate.left_id].get();
-                let right_value = self.nodes[gate.right_id].get();
-                self.nodes[gate.output_id].set(Some(left_value * right_value));
-            });
-            
-            lambda_gates.par_iter().for_each(|gate| {
-                let arguments: Vec<_> = gate.input_ids.iter().map(|&i| self.nodes[i].get()).collect();
-                self.nodes[gate.output_id].set(Some((gate.lambda)(arguments)));
-            });
+        If a gate fails, the remaining gates at its level are still evaluated, but 
+        the outputs of the failing gate and every node at a later level are cleared.
+        The gates are scheduled according to the builder's EvalStrategy. 
 
+        RETURNS:
+            Ok if every gate was evaluated, otherwise the error of the failing
+            gate with the smallest output id at the first level where one failed
+     */
+    pub fn fill_nodes(&mut self) -> Result<(), EvalError<F>> {   
+        let result = self.evaluator().fill();
+        self.changed_nodes.clear();
+        self.filled = result.is_ok();
+        result
+    }
+
+    /*
+        Fills in every node of the graph that can be computed from the inputs that 
+        are set. Nodes depending on an unset input are left unfilled instead of 
+        failing the fill, and are reported with the unset inputs they depend on. 
+        Other gate failures, such as a hint returning an error, are handled as in 
+        fill_nodes. Gates are always evaluated level by level. 
+
+        RETURNS:
+            The unfilled nodes and the unset inputs they depend on, or the error 
+            of the failing gate with the smallest output id at the first level 
+            where a gate failed for a reason other than an unset input
+     */
+    pub fn fill_nodes_partial(&mut self) -> Result<PartialFill, EvalError<F>> {
+        let result = self.evaluator().fill_partial();
+        self.changed_nodes.clear();
+        self.filled = result.as_ref().is_ok_and(|partial| partial.is_complete());
+        result
+    }
+
+    /*
+        Fills in the nodes of the graph after some inputs were set, recomputing only
+        the gates whose values depend on those inputs. The builder tracks the inputs 
+        set and the nodes overridden with override_value since the last fill, and 
+        follows the edges from each node to the nodes derived from it to find the 
+        gates to re-evaluate, level by level. 
+
+        If the nodes have not been completely filled since the last gate was added, 
+        or the last fill failed, every gate is evaluated as in fill_nodes. Errors are 
+        handled as in fill_nodes. 
+
+        RETURNS:
+            The number of gates that were evaluated, or the error of the failing
+            gate with the smallest output id at the first level where one failed
+     */
+    pub fn fill_nodes_incremental(&mut self) -> Result<usize, EvalError<F>> {
+        if !self.filled {
+            self.fill_nodes()?;
+            return Ok(self.circuit.num_gates());
         }
+
+        let index = self.reverse_index.get_or_insert_with(|| self.circuit.reverse_index());
+        let evaluator = Evaluator { circuit: &self.circuit, values: &self.values };
+        let result = evaluator.fill_cone(index, &self.changed_nodes);
+        self.changed_nodes.clear();
+        self.filled = result.is_ok();
+        result
+    }
+
+    /*
+        Fills in the nodes of the graph like fill_nodes, while checking equality 
+        assertions concurrently. Each assertion is scheduled as soon as the levels 
+        of both its nodes are complete, and is checked in parallel with the 
+        evaluation of the next level. 
+
+        With CheckMode::FailFast, the first failing assertion cancels the rest of 
+        the evaluation: no further level is started and the nodes of every level 
+        that was not evaluated are cleared. Assertions on those nodes are then 
+        reported as unevaluable. An assertion that is itself unevaluable, on an 
+        input that was never set, does not cancel the evaluation. 
+
+        ARGS:
+            mode: whether to evaluate the whole graph or stop at the first failure
+
+        RETURNS:
+            a ConstraintReport covering every assertion, or the error of the 
+            first failing gate as returned by fill_nodes
+     */
+    pub fn fill_and_check(&mut self, mode: CheckMode) -> Result<ConstraintReport<F>, EvalError<F>> {
+        let result = self.evaluator().fill_and_check(mode);
+        self.changed_nodes.clear();
+        // a fail-fast check may have stopped before the last level
+        self.filled = result.is_ok() && mode == CheckMode::Complete;
+        result
     }
 
     /*
         Async function to check that constraints between nodes are satisfied once nodes are filled in.
+        Every assertion and lookup is checked, and each failing or unevaluable one is logged. 
 
         RETURNS:
             a boolean value representing whether or not all equality constraints passed
      */
     pub async fn check_constraints(&mut self) -> bool {
-        for assertion in &self.assertions {
-            let future_left_value = async {
-                self.nodes[assertion.left_id].get()
-            }.await;
-
-            let future_right_value = async {
-                self.nodes[assertion.right_id].get()
-            }.await;
-            
-            if future_left_value != future_right_value {
-                let left_value = self.nodes[assertion.left_id].clone();
-                let right_value = self.nodes[assertion.right_id].clone();
-
-                debug!("Equality failed at nodes with id's {}, {}", left_value.id, right_value.id);
-                debug!("Node {} contains {}", left_value.id, left_value);
-                if !left_value.parents.is_empty() {
-                    debug!("Node {} is directly affected by the following nodes:", left_value.id);
-                    left_value.parents.iter().for_each(|node_id| 
-                        debug!("    Node {}: {}", *node_id, self.nodes[*node_id])
-                    );
-                } else {
-                    debug!("Node {} is an input node.", left_value.id);
-                }
-
-                debug!("Node {} contains {}", right_value.id, right_value);
-                if !right_value.parents.is_empty() {
-                    debug!("Node {} is directly affected by the following nodes:", right_value.id);
-                    right_value.parents.iter().for_each(|node_id| 
-                        debug!("    Node {}: {}", *node_id, self.nodes[*node_id])
+        let report = self.constraint_report().await;
+        let evaluator = self.evaluator();
+
+        for failure in &report.failures {
+            debug!("Equality failed at nodes with id's {}, {}", failure.left.id, failure.right.id);
+            for id in [failure.left.id, failure.right.id] {
+                debug!("Node {} contains {}", id, evaluator.describe_node(id));
+                let parents = self.circuit.parents_of(id);
+                if !parents.is_empty() {
+                    debug!("Node {} is directly affected by the following nodes:", id);
+                    parents.iter().for_each(|&parent| 
+                        debug!("    Node {}: {}", parent, evaluator.describe_node(parent))
                     );
                 } else {
-                    debug!("Node {} is an input node.", right_value.id);
+                    debug!("Node {} is an input node.", id);
                 }
-                
-                return false;
             }
         }
-        true
+
+        for assertion in &report.unevaluable {
+            debug!("Equality at nodes with id's {}, {} could not be checked since nodes {:?} are unfilled", 
+                assertion.left_id, assertion.right_id, assertion.unfilled_ids);
+        }
+
+        for failure in &report.lookup_failures {
+            debug!("Lookup into table {} failed at nodes with id's {:?} -> {}: inputs {:?}, output {:?}", 
+                failure.table, failure.input_ids, failure.output_id, failure.inputs, failure.output);
+        }
+
+        report.is_satisfied()
     }
-}
\ No newline at end of file
+
+    /*
+        Async function to check every equality assertion, in parallel, once nodes are filled in. 
+        Unlike check_constraints, the outcome is returned as structured data rather than logged. 
+
+        RETURNS:
+            a ConstraintReport with the number of passing assertions, and the node 
+            values, derivations and parent values for every failing assertion
+     */
+    pub async fn constraint_report(&self) -> ConstraintReport<F> {
+        self.evaluator().constraint_report()
+    }
+}
@@ -0,0 +1,1369 @@
+use std::{cmp::{max, min}, iter};
+use std::sync::{atomic::{AtomicUsize, Ordering}, Mutex};
+use rayon::prelude::*;
+use crate::{
+    builder::{
+        AddConstGate, AddGate, CheckMode, CustomGate, Derivation, EqualityAssertion, EvalStrategy, LambdaGate, LevelGates, 
+        LinearGate, LookupGate, MulConstGate, MultiplyGate, NegGate, Node, NodeInfo, SubGate,
+    },
+    error::{BuilderError, EvalError, HintError},
+    field::Field,
+    lookup::LookupTable,
+    report::{AssertionFailure, AssertionOutcome, BlockedNode, ConstraintReport, LookupFailure, NodeReport, PartialFill, UnevaluableAssertion},
+    store::{BatchStore, ValueStore},
+};
+
+// An immutable circuit, produced by Builder::finalize once every gate and 
+// assertion has been declared. A circuit holds no values, so it is Send + Sync 
+// and one circuit can be evaluated on many sets of inputs at once, each 
+// evaluation filling a Witness of its own. 
+// nodes: the depth, parents and derivation of every node in the circuit 
+// parent_ids: the parents of every node, stored back to back and 
+// indexed by the parents range of each node
+// gates: a vector of LevelGates. The ith element contains
+// a LevelGates structure containing all gates present at depth i.
+// assertions: a vector of equality assertions
+// input_ids: the ids of the input nodes, in the order they were declared
+// constants: the id and value of every constant node
+// strategy: how evaluate schedules the gates
+// builder_id: the id of the builder that created the circuit, 
+// stored in each of its node handles
+#[derive(Debug)]
+pub struct Circuit<F: Field> {
+    pub(crate) nodes: Vec<NodeInfo>,
+    pub(crate) parent_ids: Vec<usize>,
+    pub(crate) gates: Vec<LevelGates<F>>,
+    pub(crate) assertions: Vec<EqualityAssertion>,
+    pub(crate) input_ids: Vec<usize>,
+    pub(crate) constants: Vec<(usize, F)>,
+    pub(crate) tables: Vec<LookupTable<F>>,
+    pub(crate) strategy: EvalStrategy,
+    pub(crate) builder_id: usize,
+}
+
+// The values of every node of a circuit for one set of inputs, 
+// as returned by Circuit::evaluate
+// builder_id: the id of the builder the circuit was finalized from, to reject foreign nodes
+#[derive(Debug)]
+pub struct Witness<F: Field> {
+    values: ValueStore<F>,
+    builder_id: usize,
+}
+
+// A circuit together with the values it is being evaluated into. 
+// Builder evaluates into its own values, while Circuit::evaluate 
+// evaluates into the fresh values of a new Witness. 
+#[derive(Clone, Copy)]
+pub(crate) struct Evaluator<'a, F: Field> {
+    pub(crate) circuit: &'a Circuit<F>,
+    pub(crate) values: &'a ValueStore<F>,
+}
+
+impl<F: Field> Circuit<F> {
+    /*
+        Creates an empty circuit
+
+        ARGS:
+            builder_id: the id of the builder declaring the circuit
+     */
+    pub(crate) fn new(builder_id: usize) -> Self {
+        Circuit {
+            nodes: Vec::new(),
+            parent_ids: Vec::new(),
+            gates: Vec::new(),
+            assertions: Vec::new(),
+            input_ids: Vec::new(),
+            constants: Vec::new(),
+            tables: Vec::new(),
+            strategy: EvalStrategy::default(),
+            builder_id,
+        }
+    }
+
+    /*
+        Evaluates the circuit on a set of inputs. Each call fills a new 
+        Witness, so evaluations may run concurrently on the same circuit. 
+
+        ARGS:
+            inputs: the values of the input nodes, in the order they were declared
+
+        RETURNS:
+            The witness holding the value of every node, EvalError::InputCount if 
+            the number of inputs is wrong, or the error of the first failing gate 
+            as returned by Builder::fill_nodes
+     */
+    pub fn evaluate(&self, inputs: &[F]) -> Result<Witness<F>, EvalError<F>> {
+        if inputs.len() != self.input_ids.len() {
+            return Err(EvalError::InputCount { expected: self.input_ids.len(), given: inputs.len() });
+        }
+
+        let mut values = ValueStore::new(self.nodes.len());
+        for &(id, value) in &self.constants {
+            values.set(id, Some(value));
+        }
+        for (&id, &value) in self.input_ids.iter().zip(inputs) {
+            values.set(id, Some(value));
+        }
+
+        Evaluator { circuit: self, values: &values }.fill()?;
+        Ok(Witness { values, builder_id: self.builder_id })
+    }
+
+    /*
+        Evaluates the circuit on a batch of inputs at once. The gates are evaluated 
+        level by level as in evaluate, but each gate is evaluated for every witness 
+        before moving on, with addition and multiplication gates running a single 
+        loop over the values of their inputs across the batch. 
+
+        ARGS:
+            inputs: one row of input values per witness, each in the order the 
+            input nodes were declared
+
+        RETURNS:
+            One result per row, equal to the result of evaluate on that row
+     */
+    pub fn evaluate_batch<R: AsRef<[F]>>(&self, inputs: &[R]) -> Vec<Result<Witness<F>, EvalError<F>>> {
+        // rows with the wrong number of inputs are left out of the batch
+        let rows: Vec<&[F]> = inputs.iter()
+            .map(|row| row.as_ref())
+            .filter(|row| row.len() == self.input_ids.len())
+            .collect();
+        let mut results = self.evaluate_rows(&rows).into_iter();
+
+        inputs.iter().map(|row| match row.as_ref().len() {
+            given if given == self.input_ids.len() => results.next().unwrap(),
+            given => Err(EvalError::InputCount { expected: self.input_ids.len(), given }),
+        }).collect()
+    }
+
+    /*
+        Evaluates the circuit on a batch of rows holding one value per input node
+
+        ARGS:
+            rows: the input values of each witness
+
+        RETURNS:
+            The witness, or the error of its first failing gate, for every row
+     */
+    fn evaluate_rows(&self, rows: &[&[F]]) -> Vec<Result<Witness<F>, EvalError<F>>> {
+        let mut store = BatchStore::new(self.nodes.len(), rows.len());
+        for &(id, value) in &self.constants {
+            store.row_mut_exclusive(id).fill(value);
+        }
+        for (input, &id) in self.input_ids.iter().enumerate() {
+            for (value, row) in store.row_mut_exclusive(id).iter_mut().zip(rows) {
+                *value = row[input];
+            }
+        }
+
+        // the errors of each witness at the first level where one of its gates failed. 
+        // Later addition and multiplication gates still compute meaningless values for 
+        // a failed witness, which are discarded, but its hints are no longer called. 
+        let mut failures: Vec<Option<Vec<EvalError<F>>>> = vec![None; rows.len()];
+        for level_gate in &self.gates {
+            let failed: Vec<bool> = failures.iter().map(Option::is_some).collect();
+
+            level_gate.adder_gates.par_iter().for_each(|gate| {
+                evaluate_binary_batch(&store, gate.left_id, gate.right_id, gate.output_id, |left, right| left + right)
+            });
+            level_gate.multiplier_gates.par_iter().for_each(|gate| {
+                evaluate_binary_batch(&store, gate.left_id, gate.right_id, gate.output_id, |left, right| left * right)
+            });
+            level_gate.subtractor_gates.par_iter().for_each(|gate| {
+                evaluate_binary_batch(&store, gate.left_id, gate.right_id, gate.output_id, |left, right| left - right)
+            });
+            level_gate.negation_gates.par_iter().for_each(|gate| {
+                evaluate_unary_batch(&store, gate.input_id, gate.output_id, |value| -value)
+            });
+            level_gate.add_const_gates.par_iter().for_each(|gate| {
+                evaluate_unary_batch(&store, gate.input_id, gate.output_id, |value| value + gate.constant)
+            });
+            level_gate.mul_const_gates.par_iter().for_each(|gate| {
+                evaluate_unary_batch(&store, gate.input_id, gate.output_id, |value| value * gate.constant)
+            });
+            level_gate.linear_gates.par_iter().for_each(|gate| {
+                // SAFETY: as for evaluate_binary_batch
+                let output = unsafe { store.row_mut(gate.output_id) };
+                output.fill(gate.constant);
+                for &(coefficient, id) in &gate.terms {
+                    for (output, &input) in output.iter_mut().zip(store.row(id)) {
+                        *output = *output + coefficient * input;
+                    }
+                }
+            });
+            let lambda_errors = level_gate.lambda_gates.par_iter().flat_map_iter(|gate| {
+                evaluate_call_batch(&store, &gate.input_ids, &gate.output_ids, &failed, |arguments| call_hint(gate, arguments))
+            });
+            let custom_errors = level_gate.custom_gates.par_iter().flat_map_iter(|gate| {
+                evaluate_call_batch(&store, &gate.input_ids, &gate.output_ids, &failed, |inputs| call_gate(gate, inputs))
+            });
+            let lookup_errors = level_gate.lookup_gates.par_iter().flat_map_iter(|gate| {
+                let output_ids = std::slice::from_ref(&gate.output_id);
+                evaluate_call_batch(&store, &gate.input_ids, output_ids, &failed, |inputs| self.read_table(gate, inputs).map(|value| vec![value]))
+            });
+            let errors: Vec<(usize, EvalError<F>)> = lambda_errors.chain(custom_errors).chain(lookup_errors).collect();
+
+            for (witness, error) in errors {
+                failures[witness].get_or_insert_with(Vec::new).push(error);
+            }
+        }
+
+        failures.into_par_iter().enumerate().map(|(witness, errors)| match errors {
+            Some(errors) => Err(errors.into_iter().min_by_key(first_output).unwrap()),
+            None => {
+                let mut values = ValueStore::new(self.nodes.len());
+                for id in 0..self.nodes.len() {
+                    values.set(id, Some(store.row(id)[witness]));
+                }
+                Ok(Witness { values, builder_id: self.builder_id })
+            }
+        }).collect()
+    }
+
+    /*
+        Checks every equality assertion against the values of a witness
+
+        ARGS:
+            witness: a witness returned by evaluate on this circuit
+
+        RETURNS:
+            a ConstraintReport covering every assertion
+     */
+    pub fn constraint_report(&self, witness: &Witness<F>) -> ConstraintReport<F> {
+        Evaluator { circuit: self, values: &witness.values }.constraint_report()
+    }
+
+    /*
+        RETURNS:
+            The input nodes of the circuit, in the order evaluate expects their values
+     */
+    pub fn inputs(&self) -> Vec<Node> {
+        self.input_ids.iter().map(|&id| self.handle(id)).collect()
+    }
+
+    /*
+        RETURNS:
+            The number of nodes in the circuit
+     */
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /*
+        RETURNS:
+            The strategy evaluate uses to schedule the gates
+     */
+    pub fn eval_strategy(&self) -> EvalStrategy {
+        self.strategy
+    }
+
+    /*
+        RETURNS:
+            The depth of the node (i.e. the level it is at)
+     */
+    pub fn depth(&self, node: Node) -> u64 {
+        self.nodes[node.id].depth
+    }
+
+    /*
+        RETURNS:
+            The ids of the nodes used to derive the node's value
+     */
+    pub fn parents(&self, node: Node) -> &[usize] {
+        self.parents_of(node.id)
+    }
+
+    /*
+        RETURNS:
+            The method used to derive the node's value
+     */
+    pub fn derivation(&self, node: Node) -> Derivation {
+        self.nodes[node.id].derivation
+    }
+
+    /*
+        RETURNS:
+            The number of gates in the circuit
+     */
+    pub fn num_gates(&self) -> usize {
+        self.gates.iter().map(|level_gate| gate_refs(level_gate).count()).sum()
+    }
+
+    /*
+        Builds the reverse edges of the circuit from the parents of every node
+
+        RETURNS:
+            The children and the producing gate of every node
+     */
+    pub(crate) fn reverse_index(&self) -> ReverseIndex {
+        let mut child_offsets = vec![0; self.nodes.len() + 1];
+        for id in 0..self.nodes.len() {
+            for &parent in self.parents_of(id) {
+                child_offsets[parent + 1] += 1;
+            }
+        }
+        for id in 0..self.nodes.len() {
+            child_offsets[id + 1] += child_offsets[id];
+        }
+
+        // the outputs of a hint share their parents, so there may be 
+        // more edges than entries in parent_ids
+        let mut children = vec![0; child_offsets[self.nodes.len()]];
+        let mut next = child_offsets.clone();
+        for id in 0..self.nodes.len() {
+            for &parent in self.parents_of(id) {
+                children[next[parent]] = id;
+                next[parent] += 1;
+            }
+        }
+
+        let mut producers = vec![None; self.nodes.len()];
+        for (level, level_gate) in self.gates.iter().enumerate() {
+            for (index, gate) in level_gate.adder_gates.iter().enumerate() {
+                producers[gate.output_id] = Some(GateId::Add(level, index));
+            }
+            for (index, gate) in level_gate.multiplier_gates.iter().enumerate() {
+                producers[gate.output_id] = Some(GateId::Mul(level, index));
+            }
+            for (index, gate) in level_gate.lambda_gates.iter().enumerate() {
+                gate.output_ids.iter().for_each(|&id| producers[id] = Some(GateId::Lambda(level, index)));
+            }
+            for (index, gate) in level_gate.subtractor_gates.iter().enumerate() {
+                producers[gate.output_id] = Some(GateId::Sub(level, index));
+            }
+            for (index, gate) in level_gate.negation_gates.iter().enumerate() {
+                producers[gate.output_id] = Some(GateId::Neg(level, index));
+            }
+            for (index, gate) in level_gate.add_const_gates.iter().enumerate() {
+                producers[gate.output_id] = Some(GateId::AddConst(level, index));
+            }
+            for (index, gate) in level_gate.mul_const_gates.iter().enumerate() {
+                producers[gate.output_id] = Some(GateId::MulConst(level, index));
+            }
+            for (index, gate) in level_gate.linear_gates.iter().enumerate() {
+                producers[gate.output_id] = Some(GateId::Linear(level, index));
+            }
+            for (index, gate) in level_gate.custom_gates.iter().enumerate() {
+                gate.output_ids.iter().for_each(|&id| producers[id] = Some(GateId::Custom(level, index)));
+            }
+            for (index, gate) in level_gate.lookup_gates.iter().enumerate() {
+                producers[gate.output_id] = Some(GateId::Lookup(level, index));
+            }
+        }
+
+        ReverseIndex { child_offsets, children, producers }
+    }
+
+    /*
+        RETURNS:
+            The level of a gate and a reference to it
+     */
+    fn gate(&self, id: GateId) -> (usize, GateRef<'_, F>) {
+        match id {
+            GateId::Add(level, index) => (level, GateRef::Add(&self.gates[level].adder_gates[index])),
+            GateId::Mul(level, index) => (level, GateRef::Mul(&self.gates[level].multiplier_gates[index])),
+            GateId::Lambda(level, index) => (level, GateRef::Lambda(&self.gates[level].lambda_gates[index])),
+            GateId::Sub(level, index) => (level, GateRef::Sub(&self.gates[level].subtractor_gates[index])),
+            GateId::Neg(level, index) => (level, GateRef::Neg(&self.gates[level].negation_gates[index])),
+            GateId::AddConst(level, index) => (level, GateRef::AddConst(&self.gates[level].add_const_gates[index])),
+            GateId::MulConst(level, index) => (level, GateRef::MulConst(&self.gates[level].mul_const_gates[index])),
+            GateId::Linear(level, index) => (level, GateRef::Linear(&self.gates[level].linear_gates[index])),
+            GateId::Custom(level, index) => (level, GateRef::Custom(&self.gates[level].custom_gates[index])),
+            GateId::Lookup(level, index) => (level, GateRef::Lookup(&self.gates[level].lookup_gates[index])),
+        }
+    }
+
+    /*
+        Describes the sum computed by a linear combination gate, with the 
+        ids of its input nodes in brackets, e.g. 3 * [4] + 2 * [5] + 7
+
+        ARGS:
+            id: the id of the output node of the gate
+     */
+    fn describe_linear_gate(&self, id: usize) -> String {
+        let depth = self.nodes[id].depth as usize;
+        let gate = self.gates[depth - 1].linear_gates.iter().find(|gate| gate.output_id == id).unwrap();
+        gate.terms.iter()
+            .map(|(coefficient, input_id)| format!("{} * [{}]", coefficient, input_id))
+            .chain(iter::once(gate.constant.to_string()))
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+
+    /*
+        Reads the row of a lookup gate's table keyed by the values of its inputs
+
+        ARGS:
+            gate: the lookup gate
+            inputs: the values of the gate's input nodes
+
+        RETURNS:
+            The value of the row, or EvalError::Lookup if the table has no such row
+     */
+    fn read_table(&self, gate: &LookupGate, inputs: Vec<F>) -> Result<F, EvalError<F>> {
+        self.tables[gate.table].get(&inputs).ok_or_else(|| EvalError::Lookup {
+            table: gate.table,
+            input_ids: gate.input_ids.clone(),
+            output_ids: vec![gate.output_id],
+            inputs,
+        })
+    }
+
+    /*
+        RETURNS:
+            The name of the custom gate driving the node with the given id
+     */
+    fn custom_gate_name(&self, id: usize) -> &str {
+        let depth = self.nodes[id].depth as usize;
+        let gate = self.gates[depth - 1].custom_gates.iter().find(|gate| gate.output_ids.contains(&id)).unwrap();
+        gate.gate.name()
+    }
+
+    /*
+        RETURNS:
+            A handle to the node with the given id
+     */
+    pub(crate) fn handle(&self, id: usize) -> Node {
+        Node { id, builder_id: self.builder_id }
+    }
+
+    /*
+        RETURNS:
+            The ids of the parents of the node with the given id
+     */
+    pub(crate) fn parents_of(&self, id: usize) -> &[usize] {
+        &self.parent_ids[self.nodes[id].parents.clone()]
+    }
+}
+
+impl<F: Field> Witness<F> {
+    /*
+        Reads the value of a node
+
+        RETURNS: 
+            The value of the node, panicking if it is unfilled
+     */
+    pub fn get(&self, node: Node) -> F {
+        self.try_get(node).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /*
+        Reads the value of a node without panicking
+
+        RETURNS: 
+            The value of the node, BuilderError::ForeignNode if it belongs to 
+            another circuit, or BuilderError::UnfilledNode if it has none
+     */
+    pub fn try_get(&self, node: Node) -> Result<F, BuilderError> {
+        if node.builder_id != self.builder_id || node.id >= self.values.len() {
+            return Err(BuilderError::ForeignNode { id: node.id });
+        }
+        self.values.get(node.id).ok_or(BuilderError::UnfilledNode { id: node.id })
+    }
+}
+
+// Position of a gate in a circuit, as its type, level and 
+// index among the gates of that type at the level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GateId {
+    Add(usize, usize),
+    Mul(usize, usize),
+    Lambda(usize, usize),
+    Sub(usize, usize),
+    Neg(usize, usize),
+    AddConst(usize, usize),
+    MulConst(usize, usize),
+    Linear(usize, usize),
+    Custom(usize, usize),
+    Lookup(usize, usize),
+}
+
+// Edges of a circuit from each node to the nodes derived from it, 
+// used to find the nodes affected by a change of inputs
+// child_offsets: the children of node i are children[child_offsets[i]..child_offsets[i + 1]]
+// children: the ids of the nodes derived from each node, once per use as a parent
+// producers: the gate computing each node, or None for inputs and constants
+#[derive(Debug)]
+pub(crate) struct ReverseIndex {
+    child_offsets: Vec<usize>,
+    children: Vec<usize>,
+    producers: Vec<Option<GateId>>,
+}
+
+impl ReverseIndex {
+    /*
+        RETURNS:
+            The ids of the nodes derived directly from the node with the given id
+     */
+    fn children(&self, id: usize) -> &[usize] {
+        &self.children[self.child_offsets[id]..self.child_offsets[id + 1]]
+    }
+}
+
+// Reference to a gate of any type, used by the dataflow scheduler 
+// to treat the gates of every level as one list
+#[derive(Clone, Copy)]
+enum GateRef<'a, F: Field> {
+    Add(&'a AddGate<F>),
+    Mul(&'a MultiplyGate<F>),
+    Lambda(&'a LambdaGate<F>),
+    Sub(&'a SubGate<F>),
+    Neg(&'a NegGate<F>),
+    AddConst(&'a AddConstGate<F>),
+    MulConst(&'a MulConstGate<F>),
+    Linear(&'a LinearGate<F>),
+    Custom(&'a CustomGate<F>),
+    Lookup(&'a LookupGate),
+}
+
+/*
+    RETURNS:
+        Every gate at a level, one type after another
+ */
+fn gate_refs<F: Field>(level_gate: &LevelGates<F>) -> impl Iterator<Item = GateRef<'_, F>> {
+    level_gate.adder_gates.iter().map(GateRef::Add)
+        .chain(level_gate.multiplier_gates.iter().map(GateRef::Mul))
+        .chain(level_gate.lambda_gates.iter().map(GateRef::Lambda))
+        .chain(level_gate.subtractor_gates.iter().map(GateRef::Sub))
+        .chain(level_gate.negation_gates.iter().map(GateRef::Neg))
+        .chain(level_gate.add_const_gates.iter().map(GateRef::AddConst))
+        .chain(level_gate.mul_const_gates.iter().map(GateRef::MulConst))
+        .chain(level_gate.linear_gates.iter().map(GateRef::Linear))
+        .chain(level_gate.custom_gates.iter().map(GateRef::Custom))
+        .chain(level_gate.lookup_gates.iter().map(GateRef::Lookup))
+}
+
+impl<'a, F: Field> GateRef<'a, F> {
+    /*
+        RETURNS:
+            The ids of the input nodes of the gate, once per use
+     */
+    fn input_ids(&self) -> Vec<usize> {
+        match self {
+            GateRef::Add(gate) => vec![gate.left_id, gate.right_id],
+            GateRef::Mul(gate) => vec![gate.left_id, gate.right_id],
+            GateRef::Lambda(gate) => gate.input_ids.clone(),
+            GateRef::Sub(gate) => vec![gate.left_id, gate.right_id],
+            GateRef::Neg(gate) => vec![gate.input_id],
+            GateRef::AddConst(gate) => vec![gate.input_id],
+            GateRef::MulConst(gate) => vec![gate.input_id],
+            GateRef::Linear(gate) => gate.terms.iter().map(|&(_, id)| id).collect(),
+            GateRef::Custom(gate) => gate.input_ids.clone(),
+            GateRef::Lookup(gate) => gate.input_ids.clone(),
+        }
+    }
+
+    /*
+        RETURNS:
+            The ids of the output nodes of the gate
+     */
+    fn output_ids(&self) -> &'a [usize] {
+        match *self {
+            GateRef::Add(gate) => std::slice::from_ref(&gate.output_id),
+            GateRef::Mul(gate) => std::slice::from_ref(&gate.output_id),
+            GateRef::Lambda(gate) => &gate.output_ids,
+            GateRef::Sub(gate) => std::slice::from_ref(&gate.output_id),
+            GateRef::Neg(gate) => std::slice::from_ref(&gate.output_id),
+            GateRef::AddConst(gate) => std::slice::from_ref(&gate.output_id),
+            GateRef::MulConst(gate) => std::slice::from_ref(&gate.output_id),
+            GateRef::Linear(gate) => std::slice::from_ref(&gate.output_id),
+            GateRef::Custom(gate) => &gate.output_ids,
+            GateRef::Lookup(gate) => std::slice::from_ref(&gate.output_id),
+        }
+    }
+}
+
+// State shared by the tasks of a dataflow evaluation
+// evaluator: the circuit being evaluated, and the values it is evaluated into
+// gates: every gate of the circuit, together with its level
+// consumers: the ith element lists the gates reading node i, once per use
+// pending: the ith element counts the inputs gate i is still waiting on
+// failures: the level and error of every gate that failed
+struct DataflowScheduler<'a, F: Field> {
+    evaluator: Evaluator<'a, F>,
+    gates: Vec<(usize, GateRef<'a, F>)>,
+    consumers: Vec<Vec<usize>>,
+    pending: Vec<AtomicUsize>,
+    failures: Mutex<Vec<(usize, EvalError<F>)>>,
+}
+
+impl<'a, F: Field> DataflowScheduler<'a, F> {
+    /*
+        Builds the dependency counts of every gate in a circuit. 
+        Inputs and constants are assumed to be filled, so only 
+        the outputs of other gates are waited on. 
+     */
+    fn new(evaluator: Evaluator<'a, F>) -> Self {
+        let circuit = evaluator.circuit;
+        let gates: Vec<(usize, GateRef<'a, F>)> = circuit.gates.iter().enumerate()
+            .flat_map(|(level, level_gate)| gate_refs(level_gate).map(move |gate| (level, gate)))
+            .collect();
+
+        let mut consumers = vec![Vec::new(); circuit.nodes.len()];
+        let pending = gates.iter().enumerate().map(|(index, (_, gate))| {
+            let waiting_on: Vec<usize> = gate.input_ids().into_iter()
+                .filter(|&id| circuit.nodes[id].depth > 0)
+                .collect();
+            waiting_on.iter().for_each(|&id| consumers[id].push(index));
+            AtomicUsize::new(waiting_on.len())
+        }).collect();
+
+        DataflowScheduler { evaluator, gates, consumers, pending, failures: Mutex::new(Vec::new()) }
+    }
+
+    /*
+        Evaluates every gate, starting from the gates that only read inputs 
+        and constants. The gates downstream of a failing gate are never run. 
+
+        RETURNS:
+            The level and error of every gate that failed
+     */
+    fn run(self) -> Vec<(usize, EvalError<F>)> {
+        rayon::scope(|scope| {
+            for (index, pending) in self.pending.iter().enumerate() {
+                if pending.load(Ordering::Acquire) == 0 {
+                    let scheduler = &self;
+                    scope.spawn(move |scope| scheduler.evaluate_from(scope, index));
+                }
+            }
+        });
+        self.failures.into_inner().unwrap()
+    }
+
+    /*
+        Evaluates a gate, then every consumer it makes ready. The first ready 
+        consumer is evaluated by the same task and the others are spawned, 
+        so that a chain of gates runs without going through the thread pool. 
+
+        ARGS:
+            scope: the scope the tasks of this evaluation are spawned in
+            index: the position of the gate in self.gates
+     */
+    fn evaluate_from<'s>(&'s self, scope: &rayon::Scope<'s>, mut index: usize) {
+        loop {
+            let (level, gate) = self.gates[index];
+            if let Err(error) = self.evaluator.evaluate_gate(gate) {
+                self.failures.lock().unwrap().push((level, error));
+                return;
+            }
+
+            let mut ready = gate.output_ids().iter()
+                .flat_map(|&id| &self.consumers[id])
+                .filter(|&&consumer| self.pending[consumer].fetch_sub(1, Ordering::AcqRel) == 1);
+            let Some(&next) = ready.next() else {
+                return;
+            };
+            for &consumer in ready {
+                scope.spawn(move |scope| self.evaluate_from(scope, consumer));
+            }
+            index = next;
+        }
+    }
+}
+
+impl<'a, F: Field> Evaluator<'a, F> {
+    /*
+        Fills in every node, scheduling the gates according to the circuit's EvalStrategy
+
+        RETURNS:
+            Ok if every gate was evaluated, otherwise the error of the failing
+            gate with the smallest output id at the first level where one failed
+     */
+    pub(crate) fn fill(&self) -> Result<(), EvalError<F>> {
+        match self.circuit.strategy {
+            EvalStrategy::Level => self.fill_by_level(),
+            EvalStrategy::Dataflow => self.fill_by_dataflow(),
+        }
+    }
+
+    /*
+        Re-evaluates only the gates whose outputs depend on a set of changed nodes, 
+        level by level. Every other node is expected to already hold its value. 
+
+        ARGS:
+            index: the reverse edges of the circuit
+            changed: the ids of the nodes whose values changed
+
+        RETURNS:
+            The number of gates evaluated, or the error of the failing gate with 
+            the smallest output id at the first level where one failed
+     */
+    pub(crate) fn fill_cone(&self, index: &ReverseIndex, changed: &[usize]) -> Result<usize, EvalError<F>> {
+        // walk forward from the changed nodes, collecting the gate of every node reached
+        let mut affected = vec![false; self.circuit.nodes.len()];
+        let mut stack = changed.to_vec();
+        let mut levels: Vec<Vec<GateRef<F>>> = vec![Vec::new(); self.circuit.gates.len()];
+        while let Some(id) = stack.pop() {
+            for &child in index.children(id) {
+                if affected[child] {
+                    continue;
+                }
+                affected[child] = true;
+                stack.push(child);
+
+                // hints with several outputs are queued once, through their first output
+                if let Some(gate_id) = index.producers[child] {
+                    let (level, gate) = self.circuit.gate(gate_id);
+                    if gate.output_ids()[0] == child {
+                        levels[level].push(gate);
+                    }
+                }
+            }
+        }
+
+        let mut evaluated = 0;
+        for (level, gates) in levels.iter().enumerate() {
+            evaluated += gates.len();
+            let failure = gates.par_iter().filter_map(|&gate| self.evaluate_gate(gate).err()).min_by_key(first_output);
+            if let Some(error) = failure {
+                self.clear_nodes_deeper_than(level as u64 + 1);
+                return Err(error);
+            }
+        }
+        Ok(evaluated)
+    }
+
+    /*
+        Fills in the nodes level by level, evaluating the gates at each level in parallel
+     */
+    fn fill_by_level(&self) -> Result<(), EvalError<F>> {
+        for (level, level_gate) in self.circuit.gates.iter().enumerate() {
+            if let Some(error) = self.evaluate_level(level_gate) {
+                // values past this level would depend on the missing outputs,
+                // so clear them rather than leaving values from a previous fill
+                self.clear_nodes_deeper_than(level as u64 + 1);
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    /*
+        Fills in every node computable from the inputs that are set, level by level. 
+        Gates reading an unfilled node are skipped and their outputs cleared, 
+        while any other failure stops the fill as in fill_by_level. 
+
+        RETURNS:
+            The nodes left unfilled with the unset inputs they depend on, or the 
+            error of the failing gate with the smallest output id at the first 
+            level where a gate failed for a reason other than an unfilled input
+     */
+    pub(crate) fn fill_partial(&self) -> Result<PartialFill, EvalError<F>> {
+        let is_failure = |error: &EvalError<F>| !matches!(error, EvalError::UnfilledNode { .. });
+        for (level, level_gate) in self.circuit.gates.iter().enumerate() {
+            if let Some(error) = self.evaluate_level_where(level_gate, is_failure) {
+                self.clear_nodes_deeper_than(level as u64 + 1);
+                return Err(error);
+            }
+        }
+        Ok(self.partial_fill())
+    }
+
+    /*
+        Collects the unfilled nodes after a partial fill. Nodes are created after 
+        their parents, so the unset inputs of every node are gathered in one pass 
+        in order of id. 
+
+        RETURNS:
+            The unfilled nodes and unset inputs of the circuit
+     */
+    fn partial_fill(&self) -> PartialFill {
+        let mut missing: Vec<Vec<usize>> = vec![Vec::new(); self.circuit.nodes.len()];
+        let mut partial = PartialFill { blocked: Vec::new(), missing_input_ids: Vec::new() };
+        for (id, node) in self.circuit.nodes.iter().enumerate() {
+            if self.values.get(id).is_some() {
+                continue;
+            }
+            if node.derivation == Derivation::Input {
+                missing[id] = vec![id];
+                partial.missing_input_ids.push(id);
+                continue;
+            }
+            // filled parents have no missing inputs, so they add nothing
+            let mut missing_input_ids: Vec<usize> = self.circuit.parents_of(id).iter()
+                .flat_map(|&parent| missing[parent].iter().copied())
+                .collect();
+            missing_input_ids.sort_unstable();
+            missing_input_ids.dedup();
+            missing[id] = missing_input_ids.clone();
+            partial.blocked.push(BlockedNode { id, missing_input_ids });
+        }
+        partial
+    }
+
+    /*
+        Fills in the nodes by evaluating each gate as soon as its inputs are filled, 
+        so that a slow gate only holds back the gates that depend on it
+     */
+    fn fill_by_dataflow(&self) -> Result<(), EvalError<F>> {
+        let failures = DataflowScheduler::new(*self).run();
+
+        // a gate at the shallowest failing level only depends on gates that 
+        // succeeded, so it failed under the level strategy as well
+        match failures.into_iter().min_by_key(|(level, error)| (*level, first_output(error))) {
+            Some((level, error)) => {
+                self.clear_nodes_deeper_than(level as u64 + 1);
+                Err(error)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /*
+        Fills in every node level by level, checking each assertion in parallel 
+        with the evaluation of the level after its nodes are filled. 
+        See Builder::fill_and_check. 
+     */
+    pub(crate) fn fill_and_check(&self, mode: CheckMode) -> Result<ConstraintReport<F>, EvalError<F>> {
+        // assertions are bucketed by the depth after which both of their nodes are filled
+        let mut ready_assertions: Vec<Vec<usize>> = vec![Vec::new(); self.circuit.gates.len() + 1];
+        for (index, assertion) in self.circuit.assertions.iter().enumerate() {
+            let depth = max(self.circuit.nodes[assertion.left_id].depth, self.circuit.nodes[assertion.right_id].depth);
+            ready_assertions[depth as usize].push(index);
+        }
+
+        let mut outcomes = Vec::with_capacity(self.circuit.assertions.len());
+        let mut checked_depth = 0;
+        let mut filled_depth = 0;
+
+        // every node up to depth is filled at the start of each iteration, 
+        // so the assertions ready at depth are checked while the gates at 
+        // that level compute the nodes of the next depth
+        for (depth, ready) in ready_assertions.iter().enumerate() {
+            let (checked, failure) = rayon::join(
+                || ready.par_iter()
+                    .map(|&index| (index, self.check_assertion(index)))
+                    .collect::<Vec<_>>(),
+                || self.circuit.gates.get(depth).and_then(|level_gate| self.evaluate_level(level_gate)),
+            );
+
+            checked_depth = depth;
+            // an assertion on an unset input is unevaluable rather than failing, and does not cancel
+            let cancelled = mode == CheckMode::FailFast && checked.iter().any(|(_, outcome)| outcome.failed());
+            outcomes.extend(checked);
+
+            if let Some(error) = failure {
+                self.clear_nodes_deeper_than(depth as u64 + 1);
+                return Err(error);
+            }
+            filled_depth = min(depth + 1, self.circuit.gates.len());
+            if cancelled {
+                break;
+            }
+        }
+        self.clear_nodes_deeper_than(filled_depth as u64);
+
+        // assertions past the point of cancellation are checked on whatever was filled
+        let remaining: Vec<usize> = ready_assertions[checked_depth + 1..].concat();
+        outcomes.par_extend(remaining.into_par_iter().map(|index| (index, self.check_assertion(index))));
+        outcomes.sort_unstable_by_key(|(index, _)| *index);
+
+        // lookups are checked once evaluation has stopped, on whatever was filled
+        let report = ConstraintReport::from_outcomes(outcomes.into_iter().map(|(_, outcome)| outcome));
+        Ok(report.with_lookups(self.check_lookups()))
+    }
+
+    /*
+        Evaluates every gate at a level in parallel, one gate type at a time
+
+        ARGS:
+            level_gate: the gates at the level
+
+        RETURNS:
+            None if every gate was evaluated, otherwise the error of the 
+            failing gate with the smallest output id
+     */
+    fn evaluate_level(&self, level_gate: &LevelGates<F>) -> Option<EvalError<F>> {
+        self.evaluate_level_where(level_gate, |_| true)
+    }
+
+    /*
+        Evaluates every gate at a level in parallel, ignoring some errors
+
+        ARGS:
+            level_gate: the gates at the level
+            is_failure: whether an error returned by a gate counts as a failure
+
+        RETURNS:
+            The failure with the smallest output id, if any gate failed
+     */
+    fn evaluate_level_where(&self, level_gate: &LevelGates<F>, is_failure: impl Fn(&EvalError<F>) -> bool + Sync) -> Option<EvalError<F>> {
+        let add_gates = &level_gate.adder_gates;
+        let multiply_gates = &level_gate.multiplier_gates; 
+        let lambda_gates = &level_gate.lambda_gates; 
+        let sub_gates = &level_gate.subtractor_gates;
+        let neg_gates = &level_gate.negation_gates;
+        let add_const_gates = &level_gate.add_const_gates;
+        let mul_const_gates = &level_gate.mul_const_gates;
+        let linear_gates = &level_gate.linear_gates;
+        let custom_gates = &level_gate.custom_gates;
+        let lookup_gates = &level_gate.lookup_gates;
+        let failure = |result: Result<(), EvalError<F>>| result.err().filter(&is_failure);
+
+        // iterate over all the gates, read the inputs and drive the outputs accordingly. 
+        let failures = [
+            add_gates.par_iter().filter_map(|gate| failure(self.evaluate_add_gate(gate))).min_by_key(first_output),
+            multiply_gates.par_iter().filter_map(|gate| failure(self.evaluate_multiply_gate(gate))).min_by_key(first_output),
+            lambda_gates.par_iter().filter_map(|gate| failure(self.evaluate_lambda_gate(gate))).min_by_key(first_output),
+            sub_gates.par_iter().filter_map(|gate| failure(self.evaluate_sub_gate(gate))).min_by_key(first_output),
+            neg_gates.par_iter().filter_map(|gate| failure(self.evaluate_neg_gate(gate))).min_by_key(first_output),
+            add_const_gates.par_iter().filter_map(|gate| failure(self.evaluate_add_const_gate(gate))).min_by_key(first_output),
+            mul_const_gates.par_iter().filter_map(|gate| failure(self.evaluate_mul_const_gate(gate))).min_by_key(first_output),
+            linear_gates.par_iter().filter_map(|gate| failure(self.evaluate_linear_gate(gate))).min_by_key(first_output),
+            custom_gates.par_iter().filter_map(|gate| failure(self.evaluate_custom_gate(gate))).min_by_key(first_output),
+            lookup_gates.par_iter().filter_map(|gate| failure(self.evaluate_lookup_gate(gate))).min_by_key(first_output),
+        ];
+        failures.into_iter().flatten().min_by_key(first_output)
+    }
+
+    /*
+        Clears the values of every node deeper than the given depth
+
+        ARGS:
+            depth: nodes at this depth or shallower keep their values
+     */
+    fn clear_nodes_deeper_than(&self, depth: u64) {
+        self.circuit.nodes.par_iter().enumerate()
+            .filter(|(_, node)| node.depth > depth)
+            .for_each(|(id, _)| self.values.clear(id));
+    }
+
+    /*
+        Reads the value of a gate's input node
+
+        ARGS:
+            id: the id of the input node
+            output_ids: the output nodes of the gate, used to name it on failure
+
+        RETURNS:
+            The value of the node, or EvalError::UnfilledNode if it has none
+     */
+    fn read_input(&self, id: usize, output_ids: &[usize]) -> Result<F, EvalError<F>> {
+        self.values.get(id).ok_or_else(|| EvalError::UnfilledNode { 
+            node_id: id, 
+            output_ids: output_ids.to_vec(),
+        })
+    }
+
+    /*
+        Writes the value of a gate's output node
+
+        ARGS:
+            id: the id of the output node
+            value: the computed value, or None to clear it on failure
+     */
+    fn write_output(&self, id: usize, value: Option<F>) {
+        // SAFETY: each node is the output of a single gate, and both schedulers 
+        // only start the gates reading it once that gate has completed
+        unsafe { self.values.write(id, value) }
+    }
+
+    /*
+        Evaluates a gate of any type, clearing its outputs on failure
+     */
+    fn evaluate_gate(&self, gate: GateRef<F>) -> Result<(), EvalError<F>> {
+        match gate {
+            GateRef::Add(gate) => self.evaluate_add_gate(gate),
+            GateRef::Mul(gate) => self.evaluate_multiply_gate(gate),
+            GateRef::Lambda(gate) => self.evaluate_lambda_gate(gate),
+            GateRef::Sub(gate) => self.evaluate_sub_gate(gate),
+            GateRef::Neg(gate) => self.evaluate_neg_gate(gate),
+            GateRef::AddConst(gate) => self.evaluate_add_const_gate(gate),
+            GateRef::MulConst(gate) => self.evaluate_mul_const_gate(gate),
+            GateRef::Linear(gate) => self.evaluate_linear_gate(gate),
+            GateRef::Custom(gate) => self.evaluate_custom_gate(gate),
+            GateRef::Lookup(gate) => self.evaluate_lookup_gate(gate),
+        }
+    }
+
+    /*
+        Evaluates a gate computing its output from two input nodes, 
+        clearing the output on failure
+
+        ARGS:
+            left_id, right_id: the ids of the input nodes
+            output_id: the id of the output node
+            operation: computes the output from the values of the inputs
+     */
+    fn evaluate_binary_gate(&self, left_id: usize, right_id: usize, output_id: usize, operation: impl Fn(F, F) -> F) -> Result<(), EvalError<F>> {
+        let output_ids = std::slice::from_ref(&output_id);
+        let output = self.read_input(left_id, output_ids)
+            .and_then(|left_value| Ok(operation(left_value, self.read_input(right_id, output_ids)?)));
+        self.write_output(output_id, output.as_ref().ok().copied());
+        output.map(|_| ())
+    }
+
+    /*
+        Evaluates a gate computing its output from one input node, 
+        clearing the output on failure
+
+        ARGS:
+            input_id: the id of the input node
+            output_id: the id of the output node
+            operation: computes the output from the value of the input
+     */
+    fn evaluate_unary_gate(&self, input_id: usize, output_id: usize, operation: impl Fn(F) -> F) -> Result<(), EvalError<F>> {
+        let output = self.read_input(input_id, std::slice::from_ref(&output_id)).map(operation);
+        self.write_output(output_id, output.as_ref().ok().copied());
+        output.map(|_| ())
+    }
+
+    /*
+        Evaluates an addition gate, clearing its output on failure
+     */
+    fn evaluate_add_gate(&self, gate: &AddGate<F>) -> Result<(), EvalError<F>> {
+        self.evaluate_binary_gate(gate.left_id, gate.right_id, gate.output_id, |left, right| left + right)
+    }
+
+    /*
+        Evaluates a multiplication gate, clearing its output on failure
+     */
+    fn evaluate_multiply_gate(&self, gate: &MultiplyGate<F>) -> Result<(), EvalError<F>> {
+        self.evaluate_binary_gate(gate.left_id, gate.right_id, gate.output_id, |left, right| left * right)
+    }
+
+    /*
+        Evaluates a subtraction gate, clearing its output on failure
+     */
+    fn evaluate_sub_gate(&self, gate: &SubGate<F>) -> Result<(), EvalError<F>> {
+        self.evaluate_binary_gate(gate.left_id, gate.right_id, gate.output_id, |left, right| left - right)
+    }
+
+    /*
+        Evaluates a negation gate, clearing its output on failure
+     */
+    fn evaluate_neg_gate(&self, gate: &NegGate<F>) -> Result<(), EvalError<F>> {
+        self.evaluate_unary_gate(gate.input_id, gate.output_id, |value| -value)
+    }
+
+    /*
+        Evaluates a constant addition gate, clearing its output on failure
+     */
+    fn evaluate_add_const_gate(&self, gate: &AddConstGate<F>) -> Result<(), EvalError<F>> {
+        self.evaluate_unary_gate(gate.input_id, gate.output_id, |value| value + gate.constant)
+    }
+
+    /*
+        Evaluates a constant multiplication gate, clearing its output on failure
+     */
+    fn evaluate_mul_const_gate(&self, gate: &MulConstGate<F>) -> Result<(), EvalError<F>> {
+        self.evaluate_unary_gate(gate.input_id, gate.output_id, |value| value * gate.constant)
+    }
+
+    /*
+        Evaluates a linear combination gate, clearing its output on failure
+     */
+    fn evaluate_linear_gate(&self, gate: &LinearGate<F>) -> Result<(), EvalError<F>> {
+        let output_ids = std::slice::from_ref(&gate.output_id);
+        let sum = gate.terms.iter().try_fold(gate.constant, |sum, &(coefficient, id)| {
+            Ok(sum + coefficient * self.read_input(id, output_ids)?)
+        });
+        self.write_output(gate.output_id, sum.as_ref().ok().copied());
+        sum.map(|_| ())
+    }
+
+    /*
+        Evaluates a lambda gate by calling its hint, clearing its outputs on failure
+     */
+    fn evaluate_lambda_gate(&self, gate: &LambdaGate<F>) -> Result<(), EvalError<F>> {
+        self.evaluate_call(&gate.input_ids, &gate.output_ids, |arguments| call_hint(gate, arguments))
+    }
+
+    /*
+        Evaluates a custom gate, clearing its outputs on failure
+     */
+    fn evaluate_custom_gate(&self, gate: &CustomGate<F>) -> Result<(), EvalError<F>> {
+        self.evaluate_call(&gate.input_ids, &gate.output_ids, |inputs| call_gate(gate, inputs))
+    }
+
+    /*
+        Evaluates a lookup gate by reading the row of its table keyed 
+        by its inputs, clearing its output on failure
+     */
+    fn evaluate_lookup_gate(&self, gate: &LookupGate) -> Result<(), EvalError<F>> {
+        let output_ids = std::slice::from_ref(&gate.output_id);
+        self.evaluate_call(&gate.input_ids, output_ids, |inputs| self.circuit.read_table(gate, inputs).map(|value| vec![value]))
+    }
+
+    /*
+        Evaluates a gate computing all of its outputs with one call, 
+        clearing the outputs on failure
+
+        ARGS:
+            input_ids: the ids of the input nodes
+            output_ids: the ids of the output nodes
+            call: computes the values of the outputs from the values of the inputs
+     */
+    fn evaluate_call(&self, input_ids: &[usize], output_ids: &[usize], call: impl Fn(Vec<F>) -> Result<Vec<F>, EvalError<F>>) -> Result<(), EvalError<F>> {
+        let arguments = input_ids.iter()
+            .map(|&id| self.read_input(id, output_ids))
+            .collect::<Result<Vec<_>, _>>();
+        let result = arguments.and_then(call);
+
+        match result {
+            Ok(outputs) => {
+                output_ids.iter().zip(outputs).for_each(|(&id, value)| self.write_output(id, Some(value)));
+                Ok(())
+            }
+            Err(error) => {
+                output_ids.iter().for_each(|&id| self.write_output(id, None));
+                Err(error)
+            }
+        }
+    }
+
+    /*
+        Checks every equality assertion in parallel
+
+        RETURNS:
+            a ConstraintReport with the number of passing assertions, and the node 
+            values, derivations and parent values for every failing assertion
+     */
+    pub(crate) fn constraint_report(&self) -> ConstraintReport<F> {
+        let outcomes: Vec<AssertionOutcome<F>> = (0..self.circuit.assertions.len()).into_par_iter()
+            .map(|index| self.check_assertion(index))
+            .collect();
+        ConstraintReport::from_outcomes(outcomes).with_lookups(self.check_lookups())
+    }
+
+    /*
+        Checks that the inputs and output of every lookup gate form a row of its table
+
+        RETURNS:
+            None for each lookup that holds, and the failure of every other lookup, 
+            indexed in order of level and then of declaration within the level
+     */
+    fn check_lookups(&self) -> Vec<Option<LookupFailure<F>>> {
+        let lookups: Vec<&LookupGate> = self.circuit.gates.iter().flat_map(|level_gate| &level_gate.lookup_gates).collect();
+        lookups.into_par_iter().enumerate().map(|(index, gate)| {
+            let inputs: Vec<Option<F>> = gate.input_ids.iter().map(|&id| self.values.get(id)).collect();
+            let output = self.values.get(gate.output_id);
+            let key: Option<Vec<F>> = inputs.iter().copied().collect();
+            match (key, output) {
+                (Some(key), Some(output)) if self.circuit.tables[gate.table].contains(&key, output) => None,
+                _ => Some(LookupFailure { 
+                    index, 
+                    table: gate.table, 
+                    input_ids: gate.input_ids.clone(), 
+                    output_id: gate.output_id, 
+                    inputs, 
+                    output,
+                }),
+            }
+        }).collect()
+    }
+
+    /*
+        Checks a single equality assertion
+
+        ARGS:
+            index: the position of the assertion in circuit.assertions
+
+        RETURNS:
+            whether the assertion passed, failed or could not be evaluated
+     */
+    fn check_assertion(&self, index: usize) -> AssertionOutcome<F> {
+        let assertion = &self.circuit.assertions[index];
+        let left = self.values.get(assertion.left_id);
+        let right = self.values.get(assertion.right_id);
+        match (left, right) {
+            (Some(left), Some(right)) if left == right => AssertionOutcome::Passed,
+            (Some(_), Some(_)) => AssertionOutcome::Failed(AssertionFailure {
+                index,
+                left: self.node_report(assertion.left_id),
+                right: self.node_report(assertion.right_id),
+            }),
+            (left, right) => AssertionOutcome::Unevaluable(UnevaluableAssertion {
+                index,
+                left_id: assertion.left_id,
+                right_id: assertion.right_id,
+                unfilled_ids: [(assertion.left_id, left.is_none()), (assertion.right_id, right.is_none())]
+                    .into_iter()
+                    .filter_map(|(id, unfilled)| unfilled.then_some(id))
+                    .collect(),
+            }),
+        }
+    }
+
+    /*
+        Takes a snapshot of a node and the values of its parents
+
+        ARGS:
+            id: the id of the node
+
+        RETURNS:
+            a NodeReport describing the node
+     */
+    fn node_report(&self, id: usize) -> NodeReport<F> {
+        NodeReport {
+            id,
+            value: self.values.get(id),
+            derivation: self.circuit.nodes[id].derivation,
+            parents: self.circuit.parents_of(id).iter().map(|&parent| (parent, self.values.get(parent))).collect(),
+        }
+    }
+
+    /*
+        Describes a node for the debug logs of check_constraints
+
+        ARGS:
+            id: the id of the node
+
+        RETURNS:
+            the value, depth, id, parents and derivation of the node
+     */
+    pub(crate) fn describe_node(&self, id: usize) -> String {
+        let node = &self.circuit.nodes[id];
+        let value = match self.values.get(id) {
+            Some(value) => value.to_string(),
+            None => "unfilled".to_string(),
+        };
+        let derivation = match node.derivation {
+            Derivation::Const => "Constant".to_string(),
+            Derivation::Input => "Input".to_string(),
+            Derivation::Add => "Addition Gate".to_string(),
+            Derivation::Mul => "Multiplication Gate".to_string(),
+            Derivation::Hint => "Hint".to_string(),
+            Derivation::Sub => "Subtraction Gate".to_string(),
+            Derivation::Neg => "Negation Gate".to_string(),
+            Derivation::AddConst => "Constant Addition Gate".to_string(),
+            Derivation::MulConst => "Constant Multiplication Gate".to_string(),
+            Derivation::Linear => format!("Linear Combination Gate {}", self.circuit.describe_linear_gate(id)),
+            Derivation::Custom => format!("Custom Gate {}", self.circuit.custom_gate_name(id)),
+            Derivation::Lookup => "Lookup Gate".to_string(),
+        };
+        format!("Node {{ value: {}, depth: {}, id: {}, parents: {:?}, derivation: {} }}", 
+            value, node.depth, id, self.circuit.parents_of(id), derivation)
+    }
+}
+
+/*
+    Evaluates a gate with two input nodes for every witness of a batch
+
+    ARGS:
+        store: the values of the batch
+        left_id, right_id: the ids of the input nodes
+        output_id: the id of the output node
+        operation: computes the output from the values of the inputs
+ */
+fn evaluate_binary_batch<F: Field>(store: &BatchStore<F>, left_id: usize, right_id: usize, output_id: usize, operation: impl Fn(F, F) -> F) {
+    // SAFETY: the output row belongs to this gate alone, and is 
+    // only read by gates at later levels
+    let output = unsafe { store.row_mut(output_id) };
+    let (left, right) = (store.row(left_id), store.row(right_id));
+    for ((output, &left), &right) in output.iter_mut().zip(left).zip(right) {
+        *output = operation(left, right);
+    }
+}
+
+/*
+    Evaluates a gate with one input node for every witness of a batch
+
+    ARGS:
+        store: the values of the batch
+        input_id: the id of the input node
+        output_id: the id of the output node
+        operation: computes the output from the value of the input
+ */
+fn evaluate_unary_batch<F: Field>(store: &BatchStore<F>, input_id: usize, output_id: usize, operation: impl Fn(F) -> F) {
+    // SAFETY: as for evaluate_binary_batch
+    let output = unsafe { store.row_mut(output_id) };
+    for (output, &input) in output.iter_mut().zip(store.row(input_id)) {
+        *output = operation(input);
+    }
+}
+
+/*
+    Evaluates a lambda or custom gate for every witness of a batch that has not failed yet
+
+    ARGS:
+        store: the values of the batch
+        input_ids: the ids of the input nodes of the gate
+        output_ids: the ids of the output nodes of the gate
+        failed: whether each witness failed at an earlier level
+        call: computes the values of the outputs from the values of the inputs
+
+    RETURNS:
+        The index and error of every witness for which the gate failed
+ */
+fn evaluate_call_batch<F: Field>(
+    store: &BatchStore<F>, 
+    input_ids: &[usize], 
+    output_ids: &[usize], 
+    failed: &[bool], 
+    call: impl Fn(Vec<F>) -> Result<Vec<F>, EvalError<F>>,
+) -> Vec<(usize, EvalError<F>)> {
+    let inputs: Vec<&[F]> = input_ids.iter().map(|&id| store.row(id)).collect();
+    // SAFETY: the output rows belong to this gate alone, and are 
+    // only read by gates at later levels
+    let mut outputs: Vec<&mut [F]> = output_ids.iter().map(|&id| unsafe { store.row_mut(id) }).collect();
+
+    let mut errors = Vec::new();
+    for (witness, _) in failed.iter().enumerate().filter(|(_, &failed)| !failed) {
+        let arguments = inputs.iter().map(|row| row[witness]).collect();
+        match call(arguments) {
+            Ok(values) => outputs.iter_mut().zip(values).for_each(|(row, value)| row[witness] = value),
+            Err(error) => errors.push((witness, error)),
+        }
+    }
+    errors
+}
+
+/*
+    Calls the hint of a lambda gate, checking that it returns one value per output
+
+    ARGS:
+        gate: the lambda gate
+        arguments: the values of the gate's input nodes
+
+    RETURNS:
+        The values of the gate's output nodes, or the error describing the failed call
+ */
+fn call_hint<F: Field>(gate: &LambdaGate<F>, arguments: Vec<F>) -> Result<Vec<F>, EvalError<F>> {
+    match (gate.lambda)(&arguments) {
+        Ok(outputs) if outputs.len() == gate.output_ids.len() => Ok(outputs),
+        Ok(outputs) => Err(EvalError::HintOutputCount {
+            input_ids: gate.input_ids.clone(),
+            output_ids: gate.output_ids.clone(),
+            inputs: arguments,
+            returned: outputs.len(),
+        }),
+        Err(error) => Err(EvalError::Hint {
+            input_ids: gate.input_ids.clone(),
+            output_ids: gate.output_ids.clone(),
+            inputs: arguments,
+            error,
+        }),
+    }
+}
+
+/*
+    Evaluates a custom gate, checking that it returns one value per output
+
+    ARGS:
+        gate: the custom gate
+        inputs: the values of the gate's input nodes
+
+    RETURNS:
+        The values of the gate's output nodes, or the error describing the failed evaluation
+ */
+fn call_gate<F: Field>(gate: &CustomGate<F>, inputs: Vec<F>) -> Result<Vec<F>, EvalError<F>> {
+    let error = match gate.gate.evaluate(&inputs) {
+        Ok(outputs) if outputs.len() == gate.output_ids.len() => return Ok(outputs),
+        Ok(outputs) => HintError::new(format!("returned {} values, expected {}", outputs.len(), gate.output_ids.len())),
+        Err(error) => error,
+    };
+    Err(EvalError::Gate {
+        gate: gate.gate.name().to_string(),
+        input_ids: gate.input_ids.clone(),
+        output_ids: gate.output_ids.clone(),
+        inputs,
+        error,
+    })
+}
+
+// Orders evaluation errors by the first output id of the failing gate, 
+// so that fill_nodes reports the same error regardless of thread timing
+fn first_output<F: Field>(error: &EvalError<F>) -> Option<usize> {
+    error.output_ids().first().copied()
+}
@@ -0,0 +1,214 @@
+use std::{error::Error, fmt};
+use crate::{builder::Derivation, field::Field};
+
+// Error returned by a hint when it cannot compute its outputs, 
+// for example a division hint given a zero divisor. 
+// message: a human readable description of what went wrong
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HintError {
+    message: String,
+}
+
+impl HintError {
+    /*
+        Creates a new hint error
+
+        ARGS:
+            message: description of why the hint failed
+     */
+    pub fn new(message: impl Into<String>) -> Self {
+        HintError { message: message.into() }
+    }
+
+    /*
+        RETURNS:
+            The description of why the hint failed
+     */
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for HintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for HintError {}
+
+impl From<&str> for HintError {
+    fn from(message: &str) -> Self {
+        HintError::new(message)
+    }
+}
+
+impl From<String> for HintError {
+    fn from(message: String) -> Self {
+        HintError::new(message)
+    }
+}
+
+// Error returned by fill_nodes or Circuit::evaluate when a gate cannot be evaluated. 
+// Gates are identified by the ids of their input and output nodes. 
+// The outputs of the failing gate are left unfilled, and no gates at
+// later levels are evaluated. 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError<F: Field> {
+    // A LambdaGate's hint returned an error
+    // input_ids: ids of the hint's argument nodes 
+    // output_ids: ids of the hint's output nodes 
+    // inputs: the values the hint was called with 
+    // error: the error returned by the hint
+    Hint {
+        input_ids: Vec<usize>,
+        output_ids: Vec<usize>,
+        inputs: Vec<F>,
+        error: HintError,
+    },
+    // A LambdaGate's hint returned a different number of 
+    // values than the number of output nodes it drives
+    HintOutputCount {
+        input_ids: Vec<usize>,
+        output_ids: Vec<usize>,
+        inputs: Vec<F>,
+        returned: usize,
+    },
+    // A custom gate returned an error, or a different number of 
+    // values than the number of output nodes it drives
+    // gate: the name of the gate
+    // input_ids: ids of the gate's input nodes 
+    // output_ids: ids of the gate's output nodes 
+    // inputs: the values the gate was evaluated on 
+    // error: the error returned by the gate, or describing the output count
+    Gate {
+        gate: String,
+        input_ids: Vec<usize>,
+        output_ids: Vec<usize>,
+        inputs: Vec<F>,
+        error: HintError,
+    },
+    // A lookup gate's inputs do not form the key of any row of its table
+    // table: the index of the table
+    // input_ids: ids of the gate's input nodes 
+    // output_ids: ids of the gate's output nodes 
+    // inputs: the values of the inputs
+    Lookup {
+        table: usize,
+        input_ids: Vec<usize>,
+        output_ids: Vec<usize>,
+        inputs: Vec<F>,
+    },
+    // A gate read a node whose value was never set, 
+    // typically an input that was not given a value
+    // node_id: id of the unfilled node 
+    // output_ids: ids of the output nodes of the gate reading it
+    UnfilledNode {
+        node_id: usize,
+        output_ids: Vec<usize>,
+    },
+    // Circuit::evaluate was given a different number of 
+    // values than the circuit has input nodes
+    InputCount {
+        expected: usize,
+        given: usize,
+    },
+}
+
+impl<F: Field> EvalError<F> {
+    /*
+        RETURNS:
+            The ids of the output nodes of the gate that failed, 
+            which are empty if no gate was evaluated
+     */
+    pub fn output_ids(&self) -> &[usize] {
+        match self {
+            EvalError::Hint { output_ids, .. } => output_ids,
+            EvalError::HintOutputCount { output_ids, .. } => output_ids,
+            EvalError::Gate { output_ids, .. } => output_ids,
+            EvalError::Lookup { output_ids, .. } => output_ids,
+            EvalError::UnfilledNode { output_ids, .. } => output_ids,
+            EvalError::InputCount { .. } => &[],
+        }
+    }
+}
+
+impl<F: Field> fmt::Display for EvalError<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::Hint { input_ids, output_ids, inputs, error } => write!(f, 
+                "Hint gate with inputs {:?} and outputs {:?} failed on values [{}]: {}", 
+                input_ids, output_ids, join(inputs), error),
+            EvalError::HintOutputCount { input_ids, output_ids, inputs, returned } => write!(f, 
+                "Hint gate with inputs {:?} and outputs {:?} returned {} values on [{}], expected {}", 
+                input_ids, output_ids, returned, join(inputs), output_ids.len()),
+            EvalError::Gate { gate, input_ids, output_ids, inputs, error } => write!(f, 
+                "Gate {} with inputs {:?} and outputs {:?} failed on values [{}]: {}", 
+                gate, input_ids, output_ids, join(inputs), error),
+            EvalError::Lookup { table, input_ids, output_ids, inputs } => write!(f, 
+                "Lookup gate with inputs {:?} and outputs {:?} found no row of table {} for [{}]", 
+                input_ids, output_ids, table, join(inputs)),
+            EvalError::UnfilledNode { node_id, output_ids } => write!(f, 
+                "Gate with outputs {:?} read node {}, which is unfilled", output_ids, node_id),
+            EvalError::InputCount { expected, given } => write!(f, 
+                "Circuit has {} input nodes, but {} values were given", expected, given),
+        }
+    }
+}
+
+impl<F: Field> Error for EvalError<F> {}
+
+// Formats field elements as a comma separated list
+fn join<F: Field>(values: &[F]) -> String {
+    values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+// Error returned by the try_ variants of the Builder API when 
+// the builder or its nodes are misused. 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuilderError {
+    // The value of the node with this id has not been set or computed yet
+    UnfilledNode { id: usize },
+    // The node with this id is a constant or the output of a gate, 
+    // so its value cannot be set directly
+    DerivedNode { id: usize, derivation: Derivation },
+    // Two slices that should correspond element-wise have different lengths
+    LengthMismatch { left: usize, right: usize },
+    // A hint, custom gate or lookup was declared without any input nodes, 
+    // so it has no level to be evaluated at
+    EmptyArguments,
+    // The node with this id was not created by this builder
+    ForeignNode { id: usize },
+    // A custom gate was given a different number of input nodes than its arity
+    ArityMismatch { gate: String, expected: usize, given: usize },
+    // A node was to be decomposed into more bits than the field can hold uniquely
+    TooManyBits { bits: usize, max_bits: usize },
+    // The lookup table at this index was not registered with this builder
+    UnknownTable { index: usize },
+    // The node with this id is not the output of a hint, custom gate or lookup, 
+    // so its value is not chosen by the prover and cannot be overridden
+    NotOverridable { id: usize, derivation: Derivation },
+}
+
+impl fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuilderError::UnfilledNode { id } => write!(f, "Value unfilled at node with id {}", id),
+            BuilderError::DerivedNode { id, derivation } => write!(f, 
+                "Cannot set value of non-input node {} as it is derived ({:?})", id, derivation),
+            BuilderError::LengthMismatch { left, right } => write!(f, 
+                "Length mismatch: {} nodes on the left, {} on the right", left, right),
+            BuilderError::EmptyArguments => write!(f, "A hint, custom gate or lookup requires at least one input node"),
+            BuilderError::ForeignNode { id } => write!(f, "Node with id {} does not belong to this builder", id),
+            BuilderError::ArityMismatch { gate, expected, given } => write!(f, 
+                "Gate {} takes {} inputs, but {} nodes were given", gate, expected, given),
+            BuilderError::TooManyBits { bits, max_bits } => write!(f, 
+                "Cannot decompose a node into {} bits, the field allows at most {}", bits, max_bits),
+            BuilderError::UnknownTable { index } => write!(f, "Table {} was not registered with this builder", index),
+            BuilderError::NotOverridable { id, derivation } => write!(f, 
+                "Cannot override value of node {} as it is not a hint, custom gate or lookup output ({:?})", id, derivation),
+        }
+    }
+}
+
+impl Error for BuilderError {}
@@ -0,0 +1,126 @@
+use std::{fmt, ops::{Add, Mul, Neg, Sub}};
+use super::Field;
+
+// The BabyBear field, integers modulo p = 15 * 2^27 + 1.
+// Elements are stored as their canonical representative in [0, p).
+// The multiplicative group has a subgroup of order 2^27, which makes
+// this field popular for FFT-based proof systems.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BabyBear(u32);
+
+impl BabyBear {
+    pub const MODULUS: u32 = 0x7800_0001;
+
+    /*
+        Creates a field element from an integer
+
+        ARGS:
+            value: any integer, which is reduced modulo p
+
+        RETURNS:
+            The element value mod p
+     */
+    pub const fn new(value: u32) -> Self {
+        BabyBear(value % Self::MODULUS)
+    }
+
+    /*
+        RETURNS:
+            The canonical representative of the element in [0, p)
+     */
+    pub const fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u64> for BabyBear {
+    fn from(value: u64) -> Self {
+        BabyBear((value % Self::MODULUS as u64) as u32)
+    }
+}
+
+impl fmt::Display for BabyBear {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Add for BabyBear {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        // both operands are below 2^31, so the sum cannot overflow
+        let sum = self.0 + rhs.0;
+        if sum >= Self::MODULUS {
+            BabyBear(sum - Self::MODULUS)
+        } else {
+            BabyBear(sum)
+        }
+    }
+}
+
+impl Sub for BabyBear {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let (diff, underflow) = self.0.overflowing_sub(rhs.0);
+        if underflow {
+            BabyBear(diff.wrapping_add(Self::MODULUS))
+        } else {
+            BabyBear(diff)
+        }
+    }
+}
+
+impl Mul for BabyBear {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        BabyBear::from(self.0 as u64 * rhs.0 as u64)
+    }
+}
+
+impl Neg for BabyBear {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        if self.0 == 0 {
+            self
+        } else {
+            BabyBear(Self::MODULUS - self.0)
+        }
+    }
+}
+
+impl Field for BabyBear {
+    const NUM_BYTES: usize = 4;
+
+    fn zero() -> Self {
+        BabyBear(0)
+    }
+
+    fn one() -> Self {
+        BabyBear(1)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.pow(Self::MODULUS as u64 - 2))
+        }
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        let value = u32::from_le_bytes(bytes.try_into().ok()?);
+        if value < Self::MODULUS {
+            Some(BabyBear(value))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use std::{fmt, ops::{Add, Mul, Neg, Sub}};
+use super::Field;
+
+// The scalar field of the BN254 curve, integers modulo the 254-bit prime
+// r = 21888242871839275222246405745257275088548364400416034343698204186575808495617.
+// Elements are stored in Montgomery form (x * 2^256 mod r) as four
+// little-endian 64-bit limbs, and products use CIOS Montgomery multiplication.
+// The Montgomery representative is always fully reduced, so equality and
+// hashing can compare limbs directly.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Bn254Scalar([u64; 4]);
+
+// r as little-endian limbs
+const MODULUS: [u64; 4] = [
+    0x43e1_f593_f000_0001,
+    0x2833_e848_79b9_7091,
+    0xb850_45b6_8181_585d,
+    0x3064_4e72_e131_a029,
+];
+
+// 2^256 mod r, the Montgomery form of one
+const R: [u64; 4] = [
+    0xac96_341c_4fff_fffb,
+    0x36fc_7695_9f60_cd29,
+    0x666e_a36f_7879_462e,
+    0x0e0a_77c1_9a07_df2f,
+];
+
+// 2^512 mod r, used to move integers into Montgomery form
+const R2: [u64; 4] = [
+    0x1bb8_e645_ae21_6da7,
+    0x53fe_3ab1_e35c_59e3,
+    0x8c49_833d_53bb_8085,
+    0x0216_d0b1_7f4e_44a5,
+];
+
+// -r^-1 mod 2^64
+const INV: u64 = 0xc2e1_f593_efff_ffff;
+
+// r - 2, the exponent used for inversion
+const MODULUS_MINUS_TWO: [u64; 4] = [
+    0x43e1_f593_efff_ffff,
+    0x2833_e848_79b9_7091,
+    0xb850_45b6_8181_585d,
+    0x3064_4e72_e131_a029,
+];
+
+// Computes a + b * c + carry, returning the low and high words
+#[inline(always)]
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let wide = a as u128 + b as u128 * c as u128 + carry as u128;
+    (wide as u64, (wide >> 64) as u64)
+}
+
+// Computes a + b + carry, returning the sum and the carry out
+#[inline(always)]
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let wide = a as u128 + b as u128 + carry as u128;
+    (wide as u64, (wide >> 64) as u64)
+}
+
+// Computes a - b - borrow, returning the difference and the borrow out
+#[inline(always)]
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let wide = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (wide as u64, (wide >> 127) as u64)
+}
+
+/*
+    Subtracts two 256-bit integers
+
+    RETURNS:
+        (a - b mod 2^256, whether the subtraction borrowed)
+ */
+fn sub_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], bool) {
+    let mut out = [0u64; 4];
+    let mut borrow = 0;
+    for i in 0..4 {
+        (out[i], borrow) = sbb(a[i], b[i], borrow);
+    }
+    (out, borrow == 1)
+}
+
+/*
+    Adds two 256-bit integers
+
+    RETURNS:
+        a + b mod 2^256
+ */
+fn add_limbs(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut out = [0u64; 4];
+    let mut carry = 0;
+    for (out, (&a, &b)) in out.iter_mut().zip(a.iter().zip(b)) {
+        (*out, carry) = adc(a, b, carry);
+    }
+    out
+}
+
+/*
+    Subtracts r once if the value is not already below r
+
+    ARGS:
+        a: an integer below 2r
+
+    RETURNS:
+        a mod r
+ */
+fn subtract_modulus(a: [u64; 4]) -> [u64; 4] {
+    let (reduced, borrow) = sub_limbs(&a, &MODULUS);
+    if borrow {
+        a
+    } else {
+        reduced
+    }
+}
+
+/*
+    Montgomery multiplication using the coarsely integrated operand
+    scanning (CIOS) method
+
+    ARGS:
+        a, b: Montgomery representatives below r
+
+    RETURNS:
+        a * b * 2^-256 mod r
+ */
+fn montgomery_mul(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut t = [0u64; 6];
+    for &b_i in b {
+        let mut carry = 0;
+        for j in 0..4 {
+            (t[j], carry) = mac(t[j], a[j], b_i, carry);
+        }
+        (t[4], carry) = adc(t[4], carry, 0);
+        t[5] = carry;
+
+        // add m * r so that the lowest limb becomes zero, then shift it out
+        let m = t[0].wrapping_mul(INV);
+        let (_, mut carry) = mac(t[0], m, MODULUS[0], 0);
+        for j in 1..4 {
+            (t[j - 1], carry) = mac(t[j], m, MODULUS[j], carry);
+        }
+        (t[3], carry) = adc(t[4], carry, 0);
+        t[4] = t[5] + carry;
+    }
+
+    // r < 2^254, so the result is below 2r and t[4] is always zero here
+    subtract_modulus([t[0], t[1], t[2], t[3]])
+}
+
+impl Bn254Scalar {
+    pub const MODULUS: [u64; 4] = MODULUS;
+
+    /*
+        Creates a field element from a 256-bit integer
+
+        ARGS:
+            limbs: the integer as little-endian 64-bit limbs
+
+        RETURNS:
+            The element, or None if the integer is not below r
+     */
+    pub fn from_limbs(limbs: [u64; 4]) -> Option<Self> {
+        if sub_limbs(&limbs, &MODULUS).1 {
+            Some(Bn254Scalar(montgomery_mul(&limbs, &R2)))
+        } else {
+            None
+        }
+    }
+
+    /*
+        RETURNS:
+            The canonical representative of the element as little-endian limbs
+     */
+    pub fn to_limbs(&self) -> [u64; 4] {
+        montgomery_mul(&self.0, &[1, 0, 0, 0])
+    }
+
+    /*
+        Raises the element to a 256-bit power by square-and-multiply
+
+        ARGS:
+            exp: the exponent as little-endian limbs
+
+        RETURNS:
+            self^exp
+     */
+    fn pow_limbs(&self, exp: &[u64; 4]) -> Self {
+        let mut acc = Self::one();
+        for &limb in exp.iter().rev() {
+            for bit in (0..64).rev() {
+                acc = acc * acc;
+                if (limb >> bit) & 1 == 1 {
+                    acc = acc * *self;
+                }
+            }
+        }
+        acc
+    }
+}
+
+impl From<u64> for Bn254Scalar {
+    fn from(value: u64) -> Self {
+        Bn254Scalar(montgomery_mul(&[value, 0, 0, 0], &R2))
+    }
+}
+
+impl fmt::Display for Bn254Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // peel off base 10^19 digits by long division of the canonical limbs
+        const BASE: u64 = 10_000_000_000_000_000_000;
+        let mut limbs = self.to_limbs();
+        let mut digits = Vec::new();
+        loop {
+            let mut remainder = 0u128;
+            for limb in limbs.iter_mut().rev() {
+                let current = (remainder << 64) | *limb as u128;
+                *limb = (current / BASE as u128) as u64;
+                remainder = current % BASE as u128;
+            }
+            digits.push(remainder as u64);
+            if limbs.iter().all(|&limb| limb == 0) {
+                break;
+            }
+        }
+
+        write!(f, "{}", digits.pop().unwrap())?;
+        for digit in digits.iter().rev() {
+            write!(f, "{:019}", digit)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Bn254Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bn254Scalar({})", self)
+    }
+}
+
+impl Add for Bn254Scalar {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        // both operands are below r < 2^254, so the sum fits in 256 bits
+        Bn254Scalar(subtract_modulus(add_limbs(&self.0, &rhs.0)))
+    }
+}
+
+impl Sub for Bn254Scalar {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let (diff, borrow) = sub_limbs(&self.0, &rhs.0);
+        if borrow {
+            Bn254Scalar(add_limbs(&diff, &MODULUS))
+        } else {
+            Bn254Scalar(diff)
+        }
+    }
+}
+
+impl Mul for Bn254Scalar {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Bn254Scalar(montgomery_mul(&self.0, &rhs.0))
+    }
+}
+
+impl Neg for Bn254Scalar {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::zero() - self
+    }
+}
+
+impl Field for Bn254Scalar {
+    const NUM_BYTES: usize = 32;
+
+    fn zero() -> Self {
+        Bn254Scalar([0; 4])
+    }
+
+    fn one() -> Self {
+        Bn254Scalar(R)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            None
+        } else {
+            Some(self.pow_limbs(&MODULUS_MINUS_TWO))
+        }
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.to_limbs().iter().flat_map(|limb| limb.to_le_bytes()).collect()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::NUM_BYTES {
+            return None;
+        }
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        Bn254Scalar::from_limbs(limbs)
+    }
+}
@@ -0,0 +1,127 @@
+use std::{fmt, ops::{Add, Mul, Neg, Sub}};
+use super::Field;
+
+// Prime field of integers modulo P, for any prime P < 2^64.
+// Elements are stored as their canonical representative in [0, P).
+// Products are reduced through a u128 intermediate, so this is a
+// simple reference implementation rather than a fast one.
+//
+// P is expected to be prime; inverse() relies on Fermat's little theorem.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Fp<const P: u64>(u64);
+
+impl<const P: u64> Fp<P> {
+    pub const MODULUS: u64 = P;
+
+    /*
+        Creates a field element from an integer
+
+        ARGS:
+            value: any integer, which is reduced modulo P
+
+        RETURNS:
+            The element value mod P
+     */
+    pub const fn new(value: u64) -> Self {
+        Fp(value % P)
+    }
+
+    /*
+        RETURNS:
+            The canonical representative of the element in [0, P)
+     */
+    pub const fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl<const P: u64> From<u64> for Fp<P> {
+    fn from(value: u64) -> Self {
+        Fp::new(value)
+    }
+}
+
+impl<const P: u64> fmt::Display for Fp<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<const P: u64> Add for Fp<P> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let (sum, overflow) = self.0.overflowing_add(rhs.0);
+        if overflow || sum >= P {
+            Fp(sum.wrapping_sub(P))
+        } else {
+            Fp(sum)
+        }
+    }
+}
+
+impl<const P: u64> Sub for Fp<P> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let (diff, underflow) = self.0.overflowing_sub(rhs.0);
+        if underflow {
+            Fp(diff.wrapping_add(P))
+        } else {
+            Fp(diff)
+        }
+    }
+}
+
+impl<const P: u64> Mul for Fp<P> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Fp(((self.0 as u128 * rhs.0 as u128) % P as u128) as u64)
+    }
+}
+
+impl<const P: u64> Neg for Fp<P> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        if self.0 == 0 {
+            self
+        } else {
+            Fp(P - self.0)
+        }
+    }
+}
+
+impl<const P: u64> Field for Fp<P> {
+    const NUM_BYTES: usize = 8;
+
+    fn zero() -> Self {
+        Fp(0)
+    }
+
+    fn one() -> Self {
+        Fp::new(1)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.pow(P - 2))
+        }
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        let value = u64::from_le_bytes(bytes.try_into().ok()?);
+        if value < P {
+            Some(Fp(value))
+        } else {
+            None
+        }
+    }
+}
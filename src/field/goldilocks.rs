@@ -0,0 +1,160 @@
+use std::{fmt, ops::{Add, Mul, Neg, Sub}};
+use super::Field;
+
+// The Goldilocks field, integers modulo p = 2^64 - 2^32 + 1.
+// Elements are stored as their canonical representative in [0, p).
+// Products are reduced without division using the identities
+// 2^64 = 2^32 - 1 (mod p) and 2^96 = -1 (mod p).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Goldilocks(u64);
+
+// 2^64 - p, i.e. 2^32 - 1
+const EPSILON: u64 = 0xffff_ffff;
+
+impl Goldilocks {
+    pub const MODULUS: u64 = 0xffff_ffff_0000_0001;
+
+    /*
+        Creates a field element from an integer
+
+        ARGS:
+            value: any integer, which is reduced modulo p
+
+        RETURNS:
+            The element value mod p
+     */
+    pub const fn new(value: u64) -> Self {
+        if value >= Self::MODULUS {
+            Goldilocks(value - Self::MODULUS)
+        } else {
+            Goldilocks(value)
+        }
+    }
+
+    /*
+        RETURNS:
+            The canonical representative of the element in [0, p)
+     */
+    pub const fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+/*
+    Reduces a 128-bit integer modulo p
+
+    ARGS:
+        x: the integer to reduce
+
+    RETURNS:
+        x mod p, as a canonical representative
+ */
+fn reduce128(x: u128) -> u64 {
+    let x_lo = x as u64;
+    let x_hi = (x >> 64) as u64;
+    let x_hi_hi = x_hi >> 32;
+    let x_hi_lo = x_hi & EPSILON;
+
+    // x_hi_hi * 2^96 = -x_hi_hi
+    let (mut t0, borrow) = x_lo.overflowing_sub(x_hi_hi);
+    if borrow {
+        t0 = t0.wrapping_sub(EPSILON);
+    }
+
+    // x_hi_lo * 2^64 = x_hi_lo * (2^32 - 1)
+    let t1 = x_hi_lo * EPSILON;
+    let (sum, carry) = t0.overflowing_add(t1);
+    Goldilocks::new(sum.wrapping_add(EPSILON * carry as u64)).0
+}
+
+impl From<u64> for Goldilocks {
+    fn from(value: u64) -> Self {
+        Goldilocks::new(value)
+    }
+}
+
+impl fmt::Display for Goldilocks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Add for Goldilocks {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let (sum, overflow) = self.0.overflowing_add(rhs.0);
+        if overflow {
+            // the true sum is sum + 2^64 = sum + EPSILON, which is below p
+            Goldilocks(sum + EPSILON)
+        } else {
+            Goldilocks::new(sum)
+        }
+    }
+}
+
+impl Sub for Goldilocks {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let (diff, underflow) = self.0.overflowing_sub(rhs.0);
+        if underflow {
+            Goldilocks(diff.wrapping_add(Self::MODULUS))
+        } else {
+            Goldilocks(diff)
+        }
+    }
+}
+
+impl Mul for Goldilocks {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Goldilocks(reduce128(self.0 as u128 * rhs.0 as u128))
+    }
+}
+
+impl Neg for Goldilocks {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        if self.0 == 0 {
+            self
+        } else {
+            Goldilocks(Self::MODULUS - self.0)
+        }
+    }
+}
+
+impl Field for Goldilocks {
+    const NUM_BYTES: usize = 8;
+
+    fn zero() -> Self {
+        Goldilocks(0)
+    }
+
+    fn one() -> Self {
+        Goldilocks(1)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.pow(Self::MODULUS - 2))
+        }
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        let value = u64::from_le_bytes(bytes.try_into().ok()?);
+        if value < Self::MODULUS {
+            Some(Goldilocks(value))
+        } else {
+            None
+        }
+    }
+}
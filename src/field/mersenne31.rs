@@ -0,0 +1,146 @@
+use std::{fmt, ops::{Add, Mul, Neg, Sub}};
+use super::Field;
+
+// The Mersenne31 field, integers modulo p = 2^31 - 1.
+// Elements are stored as their canonical representative in [0, p).
+// Since 2^31 = 1 (mod p), reduction is a shift, a mask and an add.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Mersenne31(u32);
+
+impl Mersenne31 {
+    pub const MODULUS: u32 = (1 << 31) - 1;
+
+    /*
+        Creates a field element from an integer
+
+        ARGS:
+            value: any integer, which is reduced modulo p
+
+        RETURNS:
+            The element value mod p
+     */
+    pub const fn new(value: u32) -> Self {
+        Mersenne31(reduce64(value as u64))
+    }
+
+    /*
+        RETURNS:
+            The canonical representative of the element in [0, p)
+     */
+    pub const fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+/*
+    Reduces a 64-bit integer modulo p
+
+    ARGS:
+        x: the integer to reduce
+
+    RETURNS:
+        x mod p, as a canonical representative
+ */
+const fn reduce64(x: u64) -> u32 {
+    // fold twice, after which the value fits in 32 bits and is below 2p
+    let p = Mersenne31::MODULUS as u64;
+    let x = (x & p) + (x >> 31);
+    let x = (x & p) + (x >> 31);
+    if x >= p {
+        (x - p) as u32
+    } else {
+        x as u32
+    }
+}
+
+impl From<u64> for Mersenne31 {
+    fn from(value: u64) -> Self {
+        Mersenne31(reduce64(value))
+    }
+}
+
+impl fmt::Display for Mersenne31 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Add for Mersenne31 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        // both operands are below 2^31, so the sum cannot overflow
+        let sum = self.0 + rhs.0;
+        if sum >= Self::MODULUS {
+            Mersenne31(sum - Self::MODULUS)
+        } else {
+            Mersenne31(sum)
+        }
+    }
+}
+
+impl Sub for Mersenne31 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let (diff, underflow) = self.0.overflowing_sub(rhs.0);
+        if underflow {
+            Mersenne31(diff.wrapping_add(Self::MODULUS))
+        } else {
+            Mersenne31(diff)
+        }
+    }
+}
+
+impl Mul for Mersenne31 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Mersenne31(reduce64(self.0 as u64 * rhs.0 as u64))
+    }
+}
+
+impl Neg for Mersenne31 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        if self.0 == 0 {
+            self
+        } else {
+            Mersenne31(Self::MODULUS - self.0)
+        }
+    }
+}
+
+impl Field for Mersenne31 {
+    const NUM_BYTES: usize = 4;
+
+    fn zero() -> Self {
+        Mersenne31(0)
+    }
+
+    fn one() -> Self {
+        Mersenne31(1)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.pow(Self::MODULUS as u64 - 2))
+        }
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        let value = u32::from_le_bytes(bytes.try_into().ok()?);
+        if value < Self::MODULUS {
+            Some(Mersenne31(value))
+        } else {
+            None
+        }
+    }
+}
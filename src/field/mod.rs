@@ -0,0 +1,126 @@
+use std::{fmt::{Debug, Display}, hash::Hash, ops::{Add, Mul, Neg, Sub}};
+
+mod babybear;
+mod bn254;
+mod fp;
+mod goldilocks;
+mod mersenne31;
+
+pub use babybear::BabyBear;
+pub use bn254::Bn254Scalar;
+pub use fp::Fp;
+pub use goldilocks::Goldilocks;
+pub use mersenne31::Mersenne31;
+
+// Trait for the prime fields that a circuit can be computed over.
+// Every value held by a node, and every value passed to or returned
+// from a hint, is an element of the field the builder is generic over.
+// Arithmetic is provided through the standard operator traits, so
+// a + b, a * b, -a and a - b all respect modular semantics.
+pub trait Field:
+    Copy
+    + Default
+    + Debug
+    + Display
+    + PartialEq
+    + Eq
+    + Hash
+    + Send
+    + Sync
+    + 'static
+    + From<u64>
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    // Number of bytes in the canonical byte encoding of an element
+    const NUM_BYTES: usize;
+
+    /*
+        RETURNS:
+            The additive identity of the field
+     */
+    fn zero() -> Self;
+
+    /*
+        RETURNS:
+            The multiplicative identity of the field
+     */
+    fn one() -> Self;
+
+    /*
+        Computes the multiplicative inverse of an element
+
+        RETURNS:
+            Some(x) with self * x == 1, or None if self is zero
+     */
+    fn inverse(&self) -> Option<Self>;
+
+    /*
+        Encodes the element as its canonical (fully reduced) representative
+
+        RETURNS:
+            NUM_BYTES little-endian bytes
+     */
+    fn to_canonical_bytes(&self) -> Vec<u8>;
+
+    /*
+        Decodes an element from its canonical byte encoding
+
+        ARGS:
+            bytes: NUM_BYTES little-endian bytes
+
+        RETURNS:
+            The element, or None if the length is wrong or the
+            encoded integer is not smaller than the modulus
+     */
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self>;
+
+    /*
+        RETURNS:
+            Whether the element is the additive identity
+     */
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+
+    /*
+        Raises the element to a power by square-and-multiply
+
+        ARGS:
+            exp: the exponent
+
+        RETURNS:
+            self^exp
+     */
+    fn pow(&self, mut exp: u64) -> Self {
+        let mut base = *self;
+        let mut acc = Self::one();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = acc * base;
+            }
+            base = base * base;
+            exp >>= 1;
+        }
+        acc
+    }
+
+    /*
+        Reads the canonical representative of the element as an integer
+
+        RETURNS:
+            The representative if it fits in a u64, otherwise None
+     */
+    fn to_u64(&self) -> Option<u64> {
+        let bytes = self.to_canonical_bytes();
+        if bytes.iter().skip(8).any(|&byte| byte != 0) {
+            return None;
+        }
+        let mut low = [0u8; 8];
+        let len = bytes.len().min(8);
+        low[..len].copy_from_slice(&bytes[..len]);
+        Some(u64::from_le_bytes(low))
+    }
+}
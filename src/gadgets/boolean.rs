@@ -0,0 +1,101 @@
+use crate::{builder::{Builder, Node}, field::Field};
+
+// Boolean gadgets, over nodes holding 0 (false) or 1 (true). 
+// Only assert_bool declares an assertion: the other gadgets compute the 
+// boolean result when their inputs are boolean, and expect the caller to 
+// have constrained them, typically once per input with assert_bool. 
+impl<F: Field> Builder<F> {
+    /*
+        Constrains a node to be boolean with the assertion b * b == b, 
+        which only 0 and 1 satisfy in a field
+
+        ARGS:
+            b: the node to constrain
+
+        COST:
+            1 multiplication gate, 1 assertion
+     */
+    pub fn assert_bool(&mut self, b: Node) {
+        let square = self.mul(b, b);
+        self.assert_equal(square, b);
+    }
+
+    /*
+        ARGS:
+            a, b: boolean nodes
+
+        RETURNS:
+            A node holding a AND b, computed as a * b
+
+        COST:
+            1 multiplication gate
+     */
+    pub fn and(&mut self, a: Node, b: Node) -> Node {
+        self.mul(a, b)
+    }
+
+    /*
+        ARGS:
+            a, b: boolean nodes
+
+        RETURNS:
+            A node holding a OR b, computed as a + b - a * b
+
+        COST:
+            1 multiplication gate, 1 linear combination gate
+     */
+    pub fn or(&mut self, a: Node, b: Node) -> Node {
+        let product = self.mul(a, b);
+        self.linear_combination(&[(F::one(), a), (F::one(), b), (-F::one(), product)], F::zero())
+    }
+
+    /*
+        ARGS:
+            a, b: boolean nodes
+
+        RETURNS:
+            A node holding a XOR b, computed as a + b - 2 * a * b
+
+        COST:
+            1 multiplication gate, 1 linear combination gate
+     */
+    pub fn xor(&mut self, a: Node, b: Node) -> Node {
+        let product = self.mul(a, b);
+        self.linear_combination(&[(F::one(), a), (F::one(), b), (-F::from(2), product)], F::zero())
+    }
+
+    /*
+        ARGS:
+            a: a boolean node
+
+        RETURNS:
+            A node holding NOT a, computed as 1 - a
+
+        COST:
+            1 linear combination gate
+     */
+    pub fn not(&mut self, a: Node) -> Node {
+        self.linear_combination(&[(-F::one(), a)], F::one())
+    }
+
+    /*
+        Chooses between two nodes of any value according to a boolean node
+
+        ARGS:
+            cond: a boolean node
+            a: the node chosen when cond is 1
+            b: the node chosen when cond is 0
+
+        RETURNS:
+            A node holding a if cond is 1 and b if cond is 0, 
+            computed as b + cond * (a - b)
+
+        COST:
+            1 subtraction gate, 1 multiplication gate, 1 addition gate
+     */
+    pub fn select(&mut self, cond: Node, a: Node, b: Node) -> Node {
+        let difference = self.sub(a, b);
+        let chosen = self.mul(cond, difference);
+        self.add(b, chosen)
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{builder::{Builder, Node}, field::Field};
+
+// Comparison gadgets returning boolean nodes, 1 for true and 0 for false. 
+// The results are constrained, so a prover overriding the hints below 
+// cannot make them take the wrong value without failing an assertion. 
+impl<F: Field> Builder<F> {
+    /*
+        Computes whether a node is zero. A hint provides inv, the inverse of x 
+        or 0 when x is 0, and the result is out = 1 - x * inv under the assertion 
+        x * out == 0. If x is not zero the assertion forces out to 0, and if x 
+        is zero out is 1 whatever inv is. 
+
+        ARGS:
+            x: the node to test
+
+        RETURNS:
+            A boolean node holding 1 if x is zero and 0 otherwise
+
+        COST:
+            1 hint, 2 multiplication gates, 1 linear combination gate, 
+            1 assertion, and a zero constant node shared by every call
+     */
+    pub fn is_zero(&mut self, x: Node) -> Node {
+        let inverse = self.hint(&[x], |val: &[F]| Ok(val[0].inverse().unwrap_or(F::zero())));
+        let product = self.mul(x, inverse);
+        let out = self.linear_combination(&[(-F::one(), product)], F::one());
+
+        let zero = self.shared_constant(F::zero());
+        let x_out = self.mul(x, out);
+        self.assert_equal(x_out, zero);
+        out
+    }
+
+    /*
+        Computes whether two nodes are equal, as a value rather than an assertion
+
+        ARGS:
+            a, b: the nodes to compare
+
+        RETURNS:
+            A boolean node holding 1 if a equals b and 0 otherwise
+
+        COST:
+            1 subtraction gate, and the cost of is_zero
+     */
+    pub fn is_equal(&mut self, a: Node, b: Node) -> Node {
+        let difference = self.sub(a, b);
+        self.is_zero(difference)
+    }
+
+    /*
+        Computes whether a < b for nodes holding values smaller than 2^num_bits. 
+        The node d = a - b + 2^num_bits lies between 1 and 2^(num_bits + 1) - 1, 
+        and its bit num_bits is set exactly when a >= b, so d is decomposed 
+        into num_bits + 1 bits with to_bits. 
+
+        Both nodes must already be constrained to num_bits bits, for example 
+        with assert_range, otherwise d may wrap around the modulus. 
+
+        ARGS:
+            a, b: the nodes to compare, smaller than 2^num_bits
+            num_bits: the bit width of a and b, smaller than max_bits()
+
+        RETURNS:
+            A boolean node holding 1 if a < b and 0 otherwise
+
+        COST:
+            the cost of to_bits with num_bits + 1 bits, and 3 gates
+     */
+    pub fn less_than(&mut self, a: Node, b: Node, num_bits: usize) -> Node {
+        let shift = (0..num_bits).fold(F::one(), |power, _| power + power);
+        let difference = self.sub(a, b);
+        let shifted = self.add_const(difference, shift);
+        let bits = self.to_bits(shifted, num_bits + 1);
+        self.not(bits[num_bits])
+    }
+
+    /*
+        ARGS:
+            a, b: the nodes to compare, constrained to num_bits bits
+            num_bits: the bit width of a and b, smaller than max_bits()
+
+        RETURNS:
+            A node holding the larger of a and b
+
+        COST:
+            the cost of less_than, and the 3 gates of select
+     */
+    pub fn max(&mut self, a: Node, b: Node, num_bits: usize) -> Node {
+        let a_less = self.less_than(a, b, num_bits);
+        self.select(a_less, b, a)
+    }
+
+    /*
+        ARGS:
+            a, b: the nodes to compare, constrained to num_bits bits
+            num_bits: the bit width of a and b, smaller than max_bits()
+
+        RETURNS:
+            A node holding the smaller of a and b
+
+        COST:
+            the cost of less_than, and the 3 gates of select
+     */
+    pub fn min(&mut self, a: Node, b: Node, num_bits: usize) -> Node {
+        let a_less = self.less_than(a, b, num_bits);
+        self.select(a_less, a, b)
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{builder::{Builder, Node}, error::HintError, field::Field};
+
+// Field inversion and division. The quotient is computed by a hint and 
+// constrained by a multiplication, so division is exact in the field 
+// rather than the truncating integer division of a hand-written hint. 
+// A zero divisor has no inverse, so the hint fails and fill_nodes returns 
+// EvalError::Hint with the divisor among its input_ids, the quotient as its 
+// output and the message "division by zero". 
+impl<F: Field> Builder<F> {
+    /*
+        Computes the multiplicative inverse of a node, asserting x * inv == 1
+
+        ARGS:
+            x: the node to invert
+
+        RETURNS:
+            A node holding the inverse of x
+
+        COST:
+            1 hint, 1 multiplication gate, 1 assertion, and a one constant 
+            node shared by every call
+     */
+    pub fn inv(&mut self, x: Node) -> Node {
+        let inverse = self.hint(&[x], |val: &[F]| val[0].inverse().ok_or(HintError::new("division by zero")));
+        let product = self.mul(x, inverse);
+        let one = self.shared_constant(F::one());
+        self.assert_equal(product, one);
+        inverse
+    }
+
+    /*
+        Computes the quotient of two nodes, asserting b * q == a
+
+        ARGS:
+            a: the dividend
+            b: the divisor
+
+        RETURNS:
+            A node holding a / b, the field element q with b * q == a
+
+        COST:
+            1 hint, 1 multiplication gate, 1 assertion
+     */
+    pub fn div(&mut self, a: Node, b: Node) -> Node {
+        let quotient = self.hint(&[a, b], |val: &[F]| {
+            let inverse = val[1].inverse().ok_or(HintError::new("division by zero"))?;
+            Ok(val[0] * inverse)
+        });
+        let product = self.mul(b, quotient);
+        self.assert_equal(product, a);
+        quotient
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{builder::{Builder, Node}, error::BuilderError, field::Field};
+
+// A two-to-one hash that can be computed both natively and in a circuit, used
+// to hash the nodes of a Merkle tree. PoseidonParams and MimcParams implement
+// it, and any other hash built from the gates of the builder can be plugged in.
+//
+// Constants: the constant nodes the hash needs in a circuit, such as round constants
+// compress: hashes two field elements natively
+// constant_nodes: creates the constant nodes of the hash, once per circuit rather than per hash
+// compress_nodes: hashes two nodes in the circuit with the given constant nodes, 
+// matching compress once filled
+pub trait CircuitHash<F: Field> {
+    type Constants;
+
+    fn compress(&self, left: F, right: F) -> F;
+
+    fn constant_nodes(&self, builder: &mut Builder<F>) -> Self::Constants;
+
+    fn compress_nodes(&self, builder: &mut Builder<F>, constants: &Self::Constants, left: Node, right: Node) -> Node;
+}
+
+// A Merkle tree computed natively, to build the roots and witnesses that
+// merkle_verify checks in a circuit
+// levels: the hashes of every level, from the leaves up to the root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleTree<F: Field> {
+    levels: Vec<Vec<F>>,
+}
+
+// The witness of the membership of a leaf in a Merkle tree
+// leaf: the value of the leaf
+// path: the sibling of the leaf and of each of its ancestors below the root, leaf level first
+// index_bits: the bits of the position of the leaf, least significant first,
+// as 0 or 1 field elements so that they can be set on nodes directly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleWitness<F: Field> {
+    pub leaf: F,
+    pub path: Vec<F>,
+    pub index_bits: Vec<F>,
+}
+
+impl<F: Field> MerkleTree<F> {
+    /*
+        Builds a Merkle tree by hashing pairs of nodes up to the root
+
+        ARGS:
+            hash: the two-to-one hash of the tree
+            leaves: the leaves, a nonzero power of two of them
+
+        RETURNS:
+            The tree, of depth log2 of the number of leaves
+     */
+    pub fn new(hash: &impl CircuitHash<F>, leaves: Vec<F>) -> Self {
+        assert!(leaves.len().is_power_of_two(), "a Merkle tree cannot have {} leaves", leaves.len());
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let level = levels.last().unwrap().chunks(2).map(|pair| hash.compress(pair[0], pair[1])).collect();
+            levels.push(level);
+        }
+        MerkleTree { levels }
+    }
+
+    /*
+        RETURNS:
+            The number of hashes between a leaf and the root
+     */
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /*
+        RETURNS:
+            The leaves of the tree
+     */
+    pub fn leaves(&self) -> &[F] {
+        &self.levels[0]
+    }
+
+    /*
+        RETURNS:
+            The root of the tree
+     */
+    pub fn root(&self) -> F {
+        self.levels[self.depth()][0]
+    }
+
+    /*
+        Collects the witness of the membership of a leaf
+
+        ARGS:
+            index: the position of the leaf
+
+        RETURNS:
+            The leaf, the siblings on its path to the root and the bits of its index
+     */
+    pub fn witness(&self, index: usize) -> MerkleWitness<F> {
+        assert!(index < self.leaves().len(), "a tree with {} leaves has no leaf {}", self.leaves().len(), index);
+        let path = (0..self.depth()).map(|level| self.levels[level][(index >> level) ^ 1]).collect();
+        let index_bits = (0..self.depth()).map(|level| F::from(((index >> level) & 1) as u64)).collect();
+        MerkleWitness { leaf: self.leaves()[index], path, index_bits }
+    }
+}
+
+// Merkle path verification. Each level hashes the current node with its
+// sibling, ordered by the bit of the index at that level: a bit of 0 puts the
+// current node on the left, and a bit of 1 on the right. The index bits are
+// constrained to be boolean, so that select can only pick one of the two
+// orders, and the computed root is asserted equal to the root node. The 
+// constant nodes of the hash are created once and shared by every level, so 
+// for a path of depth d the gadget costs the constant nodes of the hash, 
+// d hashes, 2 * d selects (6 * d gates) and d booleanity checks (d gates and 
+// d assertions), and a final assertion.
+impl<F: Field> Builder<F> {
+    /*
+        Constrains a leaf to belong to the Merkle tree with the given root. 
+        Panics with BuilderError::LengthMismatch if the path and index bits 
+        differ in length, which try_merkle_verify returns instead. 
+
+        ARGS:
+            hash: the two-to-one hash of the tree
+            leaf: the node holding the leaf
+            path: the nodes holding the sibling at each level, leaf level first
+            index_bits: the nodes holding the bits of the position of the leaf,
+            least significant first, as many as the path
+            root: the node holding the root of the tree
+     */
+    pub fn merkle_verify(&mut self, hash: &impl CircuitHash<F>, leaf: Node, path: &[Node], index_bits: &[Node], root: Node) {
+        self.try_merkle_verify(hash, leaf, path, index_bits, root).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /*
+        Constrains a leaf to belong to the Merkle tree with the given root, 
+        without panicking on invalid arguments. Nothing is added to the 
+        circuit when an error is returned. 
+
+        ARGS:
+            hash: the two-to-one hash of the tree
+            leaf: the node holding the leaf
+            path: the nodes holding the sibling at each level, leaf level first
+            index_bits: the nodes holding the bits of the position of the leaf,
+            least significant first, as many as the path
+            root: the node holding the root of the tree
+
+        RETURNS:
+            BuilderError::LengthMismatch if the path and index bits differ in 
+            length, or BuilderError::ForeignNode if a node belongs to another builder
+     */
+    pub fn try_merkle_verify(&mut self, hash: &impl CircuitHash<F>, leaf: Node, path: &[Node], index_bits: &[Node], root: Node) -> Result<(), BuilderError> {
+        if path.len() != index_bits.len() {
+            return Err(BuilderError::LengthMismatch { left: path.len(), right: index_bits.len() });
+        }
+        [leaf, root].iter().chain(path).chain(index_bits).try_for_each(|&node| self.check_owned(node))?;
+
+        let constants = hash.constant_nodes(self);
+        let mut current = leaf;
+        for (&sibling, &bit) in path.iter().zip(index_bits) {
+            self.assert_bool(bit);
+            let left = self.select(bit, sibling, current);
+            let right = self.select(bit, current, sibling);
+            current = hash.compress_nodes(self, &constants, left, right);
+        }
+        self.assert_equal(current, root);
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{builder::{Builder, Node}, field::Field};
+use super::{merkle::CircuitHash, poseidon::{is_permutation, smallest_alpha, splitmix64}};
+
+// Parameters of the MiMC block cipher over a field. Each round computes
+// x = (x + key + c)^alpha for its round constant c, and the ciphertext is
+// x + key after the last round. Two elements are hashed with the
+// Miyaguchi-Preneel construction compress(left, right) = E_left(right) + left + right.
+// alpha: the exponent of each round, coprime with p - 1 so that x^alpha is a permutation
+// round_constants: the constant of each round, the first of which is zero
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MimcParams<F: Field> {
+    alpha: u64,
+    round_constants: Vec<F>,
+}
+
+impl<F: Field> MimcParams<F> {
+    /*
+        Creates a MiMC instance from explicit parameters. Panics if x^alpha
+        is not a permutation of the field.
+
+        ARGS:
+            alpha: the exponent of each round
+            round_constants: the constant of each round
+     */
+    pub fn new(alpha: u64, round_constants: Vec<F>) -> Self {
+        assert!(is_permutation::<F>(alpha), "x^{} is not a permutation of the field", alpha);
+        MimcParams { alpha, round_constants }
+    }
+
+    /*
+        Generates a MiMC instance for the field, with the smallest exponent that
+        permutes it and ceil(log_alpha(p)) rounds, whose constants after the
+        first are drawn from a fixed SplitMix64 sequence
+
+        RETURNS:
+            The parameters of the instance
+     */
+    pub fn generate() -> Self {
+        let alpha = smallest_alpha::<F>();
+        let modulus_bits = Builder::<F>::max_bits() + 1;
+        let num_rounds = (modulus_bits as f64 / (alpha as f64).log2()).ceil() as usize;
+
+        let mut seed = alpha;
+        let round_constants = (0..num_rounds)
+            .map(|round| if round == 0 { F::zero() } else { F::from(splitmix64(&mut seed)) })
+            .collect();
+        MimcParams::new(alpha, round_constants)
+    }
+
+    /*
+        RETURNS:
+            The exponent of each round
+     */
+    pub fn alpha(&self) -> u64 {
+        self.alpha
+    }
+
+    /*
+        RETURNS:
+            The constant of each round
+     */
+    pub fn round_constants(&self) -> &[F] {
+        &self.round_constants
+    }
+
+    /*
+        Reference implementation of the cipher, computed natively
+
+        ARGS:
+            key: the key
+            x: the plaintext
+
+        RETURNS:
+            The ciphertext
+     */
+    pub fn encrypt(&self, key: F, mut x: F) -> F {
+        for &constant in &self.round_constants {
+            x = (x + key + constant).pow(self.alpha);
+        }
+        x + key
+    }
+}
+
+impl<F: Field> CircuitHash<F> for MimcParams<F> {
+    // the round constants are folded into linear combination gates, so no constant nodes are needed
+    type Constants = ();
+
+    fn compress(&self, left: F, right: F) -> F {
+        self.encrypt(left, right) + left + right
+    }
+
+    fn constant_nodes(&self, _builder: &mut Builder<F>) {}
+
+    fn compress_nodes(&self, builder: &mut Builder<F>, _constants: &(), left: Node, right: Node) -> Node {
+        builder.mimc_compress(self, left, right)
+    }
+}
+
+// MiMC in a circuit. With r rounds and s multiplications per power
+// (3 for alpha = 5, 4 for alpha = 7), a compression costs r * (s + 1) + 1
+// gates, e.g. 23 * 5 + 1 = 116 gates for Goldilocks, and no assertions.
+impl<F: Field> Builder<F> {
+    /*
+        Encrypts a node with MiMC, matching MimcParams::encrypt
+
+        ARGS:
+            params: the MiMC instance
+            key: the node holding the key
+            x: the node holding the plaintext
+
+        RETURNS:
+            A node holding the ciphertext
+
+        COST:
+            r * (s + 1) + 1 gates
+     */
+    pub fn mimc_encrypt(&mut self, params: &MimcParams<F>, key: Node, x: Node) -> Node {
+        let x = self.mimc_rounds(params, key, x);
+        self.add(x, key)
+    }
+
+    /*
+        Hashes two nodes with MiMC in the Miyaguchi-Preneel construction,
+        matching the compress of MimcParams
+
+        ARGS:
+            params: the MiMC instance
+            left: the node used as the key
+            right: the node used as the plaintext
+
+        RETURNS:
+            A node holding E_left(right) + left + right
+
+        COST:
+            r * (s + 1) + 1 gates
+     */
+    pub fn mimc_compress(&mut self, params: &MimcParams<F>, left: Node, right: Node) -> Node {
+        // the ciphertext is x + left, so the final addition also adds left and right
+        let x = self.mimc_rounds(params, left, right);
+        self.linear_combination(&[(F::one(), x), (F::from(2), left), (F::one(), right)], F::zero())
+    }
+
+    /*
+        Applies the rounds of MiMC to a node, without the final key addition
+
+        ARGS:
+            params: the MiMC instance
+            key: the node holding the key
+            x: the node holding the plaintext
+
+        RETURNS:
+            A node holding the state after the last round
+     */
+    fn mimc_rounds(&mut self, params: &MimcParams<F>, key: Node, mut x: Node) -> Node {
+        for &constant in &params.round_constants {
+            let shifted = self.linear_combination(&[(F::one(), x), (F::one(), key)], constant);
+            x = self.pow_node(shifted, params.alpha);
+        }
+        x
+    }
+}
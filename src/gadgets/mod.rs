@@ -0,0 +1,12 @@
+// Gadgets are circuits for common operations, built on top of the gates and 
+// assertions of Builder. Each submodule adds methods to Builder, so gadgets 
+// are used like the built-in gates, e.g. builder.xor(a, b). Submodules whose 
+// gadgets take parameters are public, to export the parameter types. 
+mod bits;
+mod boolean;
+mod comparison;
+mod division;
+pub mod merkle;
+pub mod mimc;
+pub mod poseidon;
+mod sha256;
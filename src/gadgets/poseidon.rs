@@ -0,0 +1,455 @@
+use crate::{builder::{Builder, Node}, field::Field};
+use super::merkle::CircuitHash;
+
+// Parameters of a Poseidon permutation over a field. Each round adds a round
+// constant to every element of the state, raises elements to the power alpha
+// (the S-box), and multiplies the state by the MDS matrix. The first and last
+// full_rounds / 2 rounds apply the S-box to every element, and the
+// partial_rounds in between only to the first element.
+// width: the number of field elements in the state
+// full_rounds: the number of rounds with a full S-box layer, which is even
+// partial_rounds: the number of rounds with a single S-box
+// alpha: the exponent of the S-box, coprime with p - 1 so that x^alpha is a permutation
+// mds: the width x width matrix mixing the state, applied as state = mds * state
+// round_constants: the width constants added to the state in each round,
+// full_rounds + partial_rounds rows in order of the rounds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoseidonParams<F: Field> {
+    width: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+    alpha: u64,
+    mds: Vec<Vec<F>>,
+    round_constants: Vec<Vec<F>>,
+}
+
+impl<F: Field> PoseidonParams<F> {
+    /*
+        Creates a Poseidon instance from explicit parameters, such as those
+        of a standardized instance. Panics if the parameters are inconsistent.
+
+        ARGS:
+            width: the number of field elements in the state, at least 1
+            full_rounds: the number of full rounds, which must be even
+            partial_rounds: the number of partial rounds
+            alpha: the S-box exponent, at least 3 and coprime with p - 1
+            mds: a width x width matrix
+            round_constants: full_rounds + partial_rounds rows of width constants
+     */
+    pub fn new(
+        width: usize,
+        full_rounds: usize,
+        partial_rounds: usize,
+        alpha: u64,
+        mds: Vec<Vec<F>>,
+        round_constants: Vec<Vec<F>>,
+    ) -> Self {
+        assert!(width > 0, "a Poseidon state has at least one element");
+        assert!(full_rounds.is_multiple_of(2), "the {} full rounds cannot be split evenly around the partial rounds", full_rounds);
+        assert!(is_permutation::<F>(alpha), "x^{} is not a permutation of the field", alpha);
+        assert!(mds.len() == width && mds.iter().all(|row| row.len() == width), "the MDS matrix is not {} x {}", width, width);
+        assert!(
+            round_constants.len() == full_rounds + partial_rounds && round_constants.iter().all(|row| row.len() == width),
+            "expected {} rows of {} round constants", full_rounds + partial_rounds, width,
+        );
+        PoseidonParams { width, full_rounds, partial_rounds, alpha, mds, round_constants }
+    }
+
+    /*
+        Generates a Poseidon instance for the field, with the smallest S-box
+        exponent that permutes it, a Cauchy MDS matrix with entries
+        1 / (i + width + j), and round constants drawn from a fixed SplitMix64
+        sequence. The instance is deterministic, but does not match any 
+        published instance, such as those generated with the Grain LFSR of 
+        the Poseidon paper; pass those to new to permute interoperably. 
+
+        ARGS:
+            width: the number of field elements in the state, at least 1
+            full_rounds: the number of full rounds, which must be even
+            partial_rounds: the number of partial rounds
+
+        RETURNS:
+            The parameters of the instance
+     */
+    pub fn generate(width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        let alpha = Self::smallest_alpha();
+        let mds = (0..width).map(|i| (0..width).map(|j| {
+            F::from((i + width + j) as u64).inverse().expect("the field is too small for a Cauchy MDS matrix of this width")
+        }).collect()).collect();
+
+        let mut seed = ((width as u64) << 32) ^ ((full_rounds as u64) << 16) ^ partial_rounds as u64;
+        let round_constants = (0..full_rounds + partial_rounds)
+            .map(|_| (0..width).map(|_| F::from(splitmix64(&mut seed))).collect())
+            .collect();
+        PoseidonParams::new(width, full_rounds, partial_rounds, alpha, mds, round_constants)
+    }
+
+    /*
+        RETURNS:
+            The smallest exponent alpha >= 3 for which x^alpha permutes the field,
+            e.g. 7 for Goldilocks and BabyBear, and 5 for Mersenne31 and BN254
+     */
+    pub fn smallest_alpha() -> u64 {
+        smallest_alpha::<F>()
+    }
+
+    /*
+        RETURNS:
+            The number of field elements in the state
+     */
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /*
+        RETURNS:
+            The number of full rounds
+     */
+    pub fn full_rounds(&self) -> usize {
+        self.full_rounds
+    }
+
+    /*
+        RETURNS:
+            The number of partial rounds
+     */
+    pub fn partial_rounds(&self) -> usize {
+        self.partial_rounds
+    }
+
+    /*
+        RETURNS:
+            The exponent of the S-box
+     */
+    pub fn alpha(&self) -> u64 {
+        self.alpha
+    }
+
+    /*
+        RETURNS:
+            The MDS matrix, as rows
+     */
+    pub fn mds(&self) -> &[Vec<F>] {
+        &self.mds
+    }
+
+    /*
+        RETURNS:
+            The round constants, one row per round
+     */
+    pub fn round_constants(&self) -> &[Vec<F>] {
+        &self.round_constants
+    }
+
+    /*
+        Reference implementation of the permutation, computed natively
+
+        ARGS:
+            state: width field elements
+
+        RETURNS:
+            The permuted state
+     */
+    pub fn permute(&self, state: &[F]) -> Vec<F> {
+        assert_eq!(state.len(), self.width, "a Poseidon state of width {} has {} elements", self.width, state.len());
+        let mut state = state.to_vec();
+        for (round, constants) in self.round_constants.iter().enumerate() {
+            state.iter_mut().zip(constants).for_each(|(element, &constant)| *element = *element + constant);
+            let num_sboxes = if self.is_full_round(round) { self.width } else { 1 };
+            state[..num_sboxes].iter_mut().for_each(|element| *element = element.pow(self.alpha));
+            state = self.mds.iter().map(|row| {
+                row.iter().zip(&state).fold(F::zero(), |acc, (&entry, &element)| acc + entry * element)
+            }).collect();
+        }
+        state
+    }
+
+    /*
+        Reference implementation of the sponge hash, computed natively. The first
+        element of the state is the capacity, initialised to the number of inputs,
+        and the inputs are added to the other width - 1 elements in chunks, with a
+        permutation after each chunk. This sponge is specific to this crate, so 
+        its digests differ from those of circomlib even with its parameters. 
+
+        ARGS:
+            inputs: the field elements to hash
+
+        RETURNS:
+            The second element of the final state
+     */
+    pub fn hash(&self, inputs: &[F]) -> F {
+        assert!(self.width > 1, "a Poseidon sponge needs a width of at least 2");
+        let mut state = vec![F::zero(); self.width];
+        state[0] = F::from(inputs.len() as u64);
+        for chunk in sponge_chunks(inputs, self.width - 1) {
+            state[1..].iter_mut().zip(chunk).for_each(|(element, &input)| *element = *element + input);
+            state = self.permute(&state);
+        }
+        state[1]
+    }
+
+    /*
+        RETURNS:
+            Whether the round with the given index applies the S-box to every element
+     */
+    fn is_full_round(&self, round: usize) -> bool {
+        round < self.full_rounds / 2 || round >= self.full_rounds / 2 + self.partial_rounds
+    }
+}
+
+impl<F: Field> CircuitHash<F> for PoseidonParams<F> {
+    type Constants = PoseidonCompression;
+
+    fn compress(&self, left: F, right: F) -> F {
+        self.hash(&[left, right])
+    }
+
+    fn constant_nodes(&self, builder: &mut Builder<F>) -> PoseidonCompression {
+        assert!(self.width > 1, "a Poseidon sponge needs a width of at least 2");
+        PoseidonCompression {
+            constants: PoseidonConstants::new(builder, self),
+            initial_state: builder.sponge_initial_state(self, 2),
+        }
+    }
+
+    fn compress_nodes(&self, builder: &mut Builder<F>, constants: &PoseidonCompression, left: Node, right: Node) -> Node {
+        builder.absorb(self, &constants.constants, constants.initial_state.clone(), &[left, right])
+    }
+}
+
+// Poseidon permutation and sponge hash, built from addition and multiplication
+// gates and constant nodes so that they can be cross-checked against the native
+// reference implementation of PoseidonParams. Writing s for the number of
+// multiplications of the S-box (3 for alpha = 5, 4 for alpha = 7), a permutation
+// of width t with R_F full and R_P partial rounds costs
+//   (R_F + R_P) * (t + t^2 + t * (t - 1)) addition and multiplication gates for
+//   the round constants and the MDS matrix, and (R_F * t + R_P) * s S-box gates
+//   t^2 + (R_F + R_P) * t constant nodes
+// and declares no assertions, since every gate is deterministic.
+impl<F: Field> Builder<F> {
+    /*
+        Applies the Poseidon permutation to a state of nodes
+
+        ARGS:
+            params: the Poseidon instance
+            state: params.width() nodes
+
+        RETURNS:
+            params.width() nodes holding the permuted state
+     */
+    pub fn poseidon_permutation(&mut self, params: &PoseidonParams<F>, state: &[Node]) -> Vec<Node> {
+        assert_eq!(state.len(), params.width, "a Poseidon state of width {} has {} elements", params.width, state.len());
+        let constants = PoseidonConstants::new(self, params);
+        self.permute_nodes(params, &constants, state.to_vec())
+    }
+
+    /*
+        Hashes nodes with the Poseidon sponge, matching PoseidonParams::hash
+
+        ARGS:
+            params: the Poseidon instance, of width at least 2
+            inputs: the nodes to hash
+
+        RETURNS:
+            A node holding the hash of the inputs
+
+        COST:
+            ceil(len / (width - 1)) permutations sharing a single set of constant nodes,
+            len additions to absorb the inputs, and width constant nodes for the initial state
+     */
+    pub fn poseidon_hash(&mut self, params: &PoseidonParams<F>, inputs: &[Node]) -> Node {
+        assert!(params.width > 1, "a Poseidon sponge needs a width of at least 2");
+        let constants = PoseidonConstants::new(self, params);
+        let state = self.sponge_initial_state(params, inputs.len());
+        self.absorb(params, &constants, state, inputs)
+    }
+
+    /*
+        Creates the constant nodes of the initial state of a sponge
+
+        ARGS:
+            params: the Poseidon instance
+            num_inputs: the number of inputs to hash, held by the capacity element
+
+        RETURNS:
+            params.width() constant nodes
+     */
+    fn sponge_initial_state(&mut self, params: &PoseidonParams<F>, num_inputs: usize) -> Vec<Node> {
+        (0..params.width).map(|i| {
+            let initial = if i == 0 { F::from(num_inputs as u64) } else { F::zero() };
+            self.constant(initial)
+        }).collect()
+    }
+
+    /*
+        Absorbs nodes into a sponge, permuting the state after each chunk
+
+        ARGS:
+            params: the Poseidon instance
+            constants: the constant nodes of the instance
+            state: the initial state of the sponge
+            inputs: the nodes to hash
+
+        RETURNS:
+            A node holding the second element of the final state
+     */
+    fn absorb(&mut self, params: &PoseidonParams<F>, constants: &PoseidonConstants, mut state: Vec<Node>, inputs: &[Node]) -> Node {
+        for chunk in sponge_chunks(inputs, params.width - 1) {
+            for (i, &input) in chunk.iter().enumerate() {
+                state[i + 1] = self.add(state[i + 1], input);
+            }
+            state = self.permute_nodes(params, constants, state);
+        }
+        state[1]
+    }
+
+    /*
+        Applies the rounds of the permutation to a state of nodes
+
+        ARGS:
+            params: the Poseidon instance
+            constants: the constant nodes of the instance
+            state: params.width() nodes
+
+        RETURNS:
+            The permuted state
+     */
+    fn permute_nodes(&mut self, params: &PoseidonParams<F>, constants: &PoseidonConstants, mut state: Vec<Node>) -> Vec<Node> {
+        for (round, round_constants) in constants.round_constants.iter().enumerate() {
+            for (element, &constant) in state.iter_mut().zip(round_constants) {
+                *element = self.add(*element, constant);
+            }
+            let num_sboxes = if params.is_full_round(round) { params.width } else { 1 };
+            for element in &mut state[..num_sboxes] {
+                *element = self.pow_node(*element, params.alpha);
+            }
+            state = constants.mds.iter().map(|row| {
+                let products: Vec<Node> = row.iter().zip(&state).map(|(&entry, &element)| self.mul(entry, element)).collect();
+                products.into_iter().reduce(|acc, product| self.add(acc, product)).unwrap()
+            }).collect();
+        }
+        state
+    }
+
+    /*
+        Raises a node to a constant power by square-and-multiply
+
+        ARGS:
+            node: the base
+            exp: the exponent, at least 1
+
+        RETURNS:
+            A node holding node^exp
+     */
+    pub(crate) fn pow_node(&mut self, node: Node, mut exp: u64) -> Node {
+        let mut base = node;
+        let mut acc = None;
+        loop {
+            if exp & 1 == 1 {
+                acc = Some(acc.map_or(base, |acc| self.mul(acc, base)));
+            }
+            exp >>= 1;
+            if exp == 0 {
+                return acc.unwrap();
+            }
+            base = self.mul(base, base);
+        }
+    }
+}
+
+// The constant nodes of a Poseidon two-to-one hash, created once by 
+// merkle_verify and shared by the hashes of every level
+// constants: the constant nodes of the permutation
+// initial_state: the constant nodes of the initial state of a sponge of two inputs
+pub struct PoseidonCompression {
+    constants: PoseidonConstants,
+    initial_state: Vec<Node>,
+}
+
+// The constant nodes of a Poseidon instance, created once and shared by
+// every permutation of a hash
+// mds: a constant node for each entry of the MDS matrix
+// round_constants: a constant node for each round constant
+struct PoseidonConstants {
+    mds: Vec<Vec<Node>>,
+    round_constants: Vec<Vec<Node>>,
+}
+
+impl PoseidonConstants {
+    /*
+        Creates the constant nodes of a Poseidon instance
+
+        ARGS:
+            builder: the builder to create the nodes in
+            params: the Poseidon instance
+     */
+    fn new<F: Field>(builder: &mut Builder<F>, params: &PoseidonParams<F>) -> Self {
+        let mut constants = |rows: &[Vec<F>]| -> Vec<Vec<Node>> {
+            rows.iter().map(|row| row.iter().map(|&value| builder.constant(value)).collect()).collect()
+        };
+        PoseidonConstants { mds: constants(&params.mds), round_constants: constants(&params.round_constants) }
+    }
+}
+
+/*
+    Splits the inputs of a sponge into the chunks absorbed before each permutation
+
+    ARGS:
+        inputs: the inputs to absorb
+        rate: the number of inputs absorbed per permutation
+
+    RETURNS:
+        The chunks of at most rate inputs, or a single empty chunk if there are
+        no inputs, so that every hash applies at least one permutation
+ */
+fn sponge_chunks<T>(inputs: &[T], rate: usize) -> Vec<&[T]> {
+    if inputs.is_empty() {
+        return vec![inputs];
+    }
+    inputs.chunks(rate).collect()
+}
+
+/*
+    RETURNS:
+        The smallest exponent alpha >= 3 for which x^alpha permutes the field
+ */
+pub(super) fn smallest_alpha<F: Field>() -> u64 {
+    (3..).step_by(2).find(|&alpha| is_permutation::<F>(alpha)).unwrap()
+}
+
+/*
+    Checks that x^alpha is a permutation of the field, i.e. that alpha is
+    coprime with p - 1, by reducing the bytes of p - 1 modulo alpha
+
+    RETURNS:
+        Whether alpha is at least 3 and coprime with p - 1
+ */
+pub(super) fn is_permutation<F: Field>(alpha: u64) -> bool {
+    if alpha < 3 {
+        return false;
+    }
+    let remainder = (-F::one()).to_canonical_bytes().iter().rev()
+        .fold(0u128, |acc, &byte| ((acc << 8) | byte as u128) % alpha as u128) as u64;
+    let (mut a, mut b) = (alpha, remainder);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a == 1
+}
+
+/*
+    Steps the SplitMix64 generator used to derive round constants
+
+    ARGS:
+        state: the state of the generator, advanced in place
+
+    RETURNS:
+        The next pseudorandom word
+ */
+pub(super) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
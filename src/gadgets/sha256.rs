@@ -0,0 +1,190 @@
+use crate::{builder::{Builder, Node}, field::Field};
+
+// The round constants of SHA-256, from FIPS 180-4
+const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// The initial hash value of SHA-256, from FIPS 180-4
+const INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+// The number of bits a sum of words is decomposed into, which needs the field to hold 35 bits
+const MAX_SUM_BITS: usize = 35;
+
+// SHA-256 over bit nodes. Words are handled as 32 bits, least significant
+// first as returned by to_bits, and converted from and to the big-endian bit
+// order of messages and digests at the boundary. Rotations and shifts only
+// reorder bits, Ch and Maj are built with select, the sigma functions with
+// xor, and additions modulo 2^32 as a linear combination of the bits of every
+// operand decomposed with to_bits, keeping the low 32 bits and discarding
+// the carries. The input bits are assumed to be constrained to be boolean
+// where they enter the circuit, as for the boolean gadgets.
+//
+// A compression costs 50736 gates and 6560 assertions:
+//   message schedule: 48 words of 267 gates and 35 assertions
+//   rounds: 64 rounds of 588 gates and 72 assertions
+//   final addition: 8 words of 36 gates and 34 assertions
+// and additions take a 35-bit decomposition, so the field needs max_bits() >= 35,
+// e.g. Goldilocks or BN254 but not BabyBear or Mersenne31.
+impl<F: Field> Builder<F> {
+    /*
+        Creates constant nodes holding the initial hash value of SHA-256
+
+        RETURNS:
+            256 constant bit nodes, in the big-endian bit order of sha256_compress
+     */
+    pub fn sha256_initial_state(&mut self) -> Vec<Node> {
+        let zero = self.constant(F::zero());
+        let one = self.constant(F::one());
+        INITIAL_STATE.iter()
+            .flat_map(|&word| (0..32).rev().map(move |i| (word >> i) & 1 == 1))
+            .map(|bit| if bit { one } else { zero })
+            .collect()
+    }
+
+    /*
+        Applies the SHA-256 compression function to a chaining value and a block
+
+        ARGS:
+            state: 256 bit nodes holding the chaining value, eight big-endian words
+            block: 512 bit nodes holding the message block, sixteen big-endian words
+
+        RETURNS:
+            256 bit nodes holding the next chaining value, which after the last
+            block is the digest, most significant bit of its first byte first
+
+        COST:
+            50736 gates and 6560 assertions
+     */
+    pub fn sha256_compress(&mut self, state: &[Node], block: &[Node]) -> Vec<Node> {
+        assert_eq!(state.len(), 256, "a SHA-256 state has 256 bits, not {}", state.len());
+        assert_eq!(block.len(), 512, "a SHA-256 block has 512 bits, not {}", block.len());
+        assert!(Self::max_bits() >= MAX_SUM_BITS, "SHA-256 additions need a field holding {} bits", MAX_SUM_BITS);
+
+        let initial: Vec<Vec<Node>> = state.chunks(32).map(flip_bit_order).collect();
+        let mut schedule: Vec<Vec<Node>> = block.chunks(32).map(flip_bit_order).collect();
+        for t in 16..64 {
+            let s0 = self.sigma(&schedule[t - 15], &[7, 18], Some(3));
+            let s1 = self.sigma(&schedule[t - 2], &[17, 19], Some(10));
+            let word = self.add_words(&[&s1, &schedule[t - 7], &s0, &schedule[t - 16]], 0);
+            schedule.push(word);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h]: [Vec<Node>; 8] = initial.clone().try_into().unwrap();
+        for (word, &constant) in schedule.iter().zip(&ROUND_CONSTANTS) {
+            let s1 = self.sigma(&e, &[6, 11, 25], None);
+            let ch: Vec<Node> = (0..32).map(|i| self.select(e[i], f[i], g[i])).collect();
+            let s0 = self.sigma(&a, &[2, 13, 22], None);
+            let maj: Vec<Node> = (0..32).map(|i| {
+                let differ = self.xor(a[i], b[i]);
+                self.select(differ, c[i], a[i])
+            }).collect();
+
+            let new_e = self.add_words(&[&d, &h, &s1, &ch, word], constant);
+            let new_a = self.add_words(&[&h, &s1, &ch, word, &s0, &maj], constant);
+            (h, g, f, e, d, c, b, a) = (g, f, e, new_e, c, b, a, new_a);
+        }
+
+        let working = [a, b, c, d, e, f, g, h];
+        initial.iter().zip(&working)
+            .flat_map(|(initial, working)| flip_bit_order(&self.add_words(&[initial, working], 0)))
+            .collect()
+    }
+
+    /*
+        Hashes a message of bit nodes with SHA-256, padding it with constant nodes
+
+        ARGS:
+            message: the bits of the message, most significant bit of its first byte first
+
+        RETURNS:
+            256 bit nodes holding the digest
+
+        COST:
+            The cost of sha256_compress for each of the (len + 64) / 512 + 1 blocks
+     */
+    pub fn sha256(&mut self, message: &[Node]) -> Vec<Node> {
+        let zero = self.constant(F::zero());
+        let one = self.constant(F::one());
+        let length = message.len() as u64;
+
+        // a one, zeros up to 448 bits modulo 512, and the length as a 64-bit word
+        let mut padded = message.to_vec();
+        padded.push(one);
+        while padded.len() % 512 != 448 {
+            padded.push(zero);
+        }
+        padded.extend((0..64).rev().map(|i| if (length >> i) & 1 == 1 { one } else { zero }));
+
+        let initial = self.sha256_initial_state();
+        padded.chunks(512).fold(initial, |state, block| self.sha256_compress(&state, block))
+    }
+
+    /*
+        Computes a sigma function of SHA-256, the XOR of rotations and an 
+        optional shift of a word. Bits shifted in are zero, so they are left 
+        out of the XOR rather than held by constant nodes. 
+
+        ARGS:
+            word: 32 bits, least significant first
+            rotations: the amounts of the rotations to the right
+            shift: the amount of the shift to the right, if any
+
+        RETURNS:
+            32 bits, least significant first
+     */
+    fn sigma(&mut self, word: &[Node], rotations: &[usize], shift: Option<usize>) -> Vec<Node> {
+        (0..32).map(|i| {
+            let rotated = rotations.iter().map(|&rotation| word[(i + rotation) % 32]);
+            let shifted = shift.filter(|&shift| i + shift < 32).map(|shift| word[i + shift]);
+            rotated.chain(shifted).reduce(|acc, bit| self.xor(acc, bit)).unwrap()
+        }).collect()
+    }
+
+    /*
+        Adds words modulo 2^32, decomposing the sum of their bits and a constant
+        and discarding the carries
+
+        ARGS:
+            words: the words to add, 32 bits each, least significant first
+            constant: a constant added to the words
+
+        RETURNS:
+            32 bits holding the sum modulo 2^32, least significant first
+
+        COST:
+            n + 3 gates and n + 1 assertions, with n the bit length of the largest
+            possible sum, i.e. 33 bits for two words and 35 bits for six words and a constant
+     */
+    fn add_words(&mut self, words: &[&[Node]], constant: u32) -> Vec<Node> {
+        let terms: Vec<(F, Node)> = words.iter()
+            .flat_map(|word| word.iter().enumerate().map(|(i, &bit)| (F::from(1 << i), bit)))
+            .collect();
+        let sum = self.linear_combination(&terms, F::from(constant as u64));
+
+        let max_sum = words.len() as u64 * u32::MAX as u64 + constant as u64;
+        let mut bits = self.to_bits(sum, 64 - max_sum.leading_zeros() as usize);
+        bits.truncate(32);
+        bits
+    }
+}
+
+/*
+    Converts the 32 bits of a word between big-endian order, as in messages 
+    and digests, and the least significant first order of to_bits
+
+    RETURNS:
+        The bits of the word in the other order
+ */
+fn flip_bit_order(bits: &[Node]) -> Vec<Node> {
+    bits.iter().rev().copied().collect()
+}
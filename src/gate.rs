@@ -0,0 +1,28 @@
+use crate::{builder::{Builder, Node}, error::HintError, field::Field};
+
+// A user-defined operation with its own constraints, registered with Builder::custom_gate.
+// Unlike a hint, whose outputs are only trusted once assertions are added by hand, a
+// gate declares the constraints tying its outputs to its inputs, and the builder adds
+// them every time the gate is used. The gate is shared behind an Arc and evaluated in
+// parallel by fill_nodes, so it is required to be Send + Sync.
+//
+// name: the name of the gate, shown when describing its output nodes and in errors
+// arity: the number of input nodes the gate takes
+// num_outputs: the number of output nodes the gate drives
+// evaluate: computes the values of the outputs from the values of the inputs,
+// returning num_outputs values, or a HintError on inputs it cannot handle
+// constraints: declares the assertions that hold between the inputs and outputs,
+// built with the gates and assertions of the builder
+pub trait Gate<F: Field>: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn arity(&self) -> usize;
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn evaluate(&self, inputs: &[F]) -> Result<Vec<F>, HintError>;
+
+    fn constraints(&self, builder: &mut Builder<F>, inputs: &[Node], outputs: &[Node]);
+}
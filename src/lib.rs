@@ -1 +1,9 @@
-pub mod builder;
\ No newline at end of file
+pub mod builder;
+pub mod circuit;
+pub mod error;
+pub mod field;
+pub mod gadgets;
+pub mod gate;
+pub mod lookup;
+pub mod report;
+mod store;
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use crate::field::Field;
+
+// The largest number of key bits, arity * num_bits, of a table built by from_fn, 
+// which then has at most 2^24 (about 16 million) rows
+pub const MAX_TABLE_BITS: u32 = 24;
+
+// Handle to a table registered with Builder::register_table, used to build lookup gates on it
+// index: the position of the table in the circuit
+// builder_id: the builder the table was registered with, used to reject tables of other builders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TableId {
+    pub(crate) index: usize,
+    pub(crate) builder_id: usize,
+}
+
+// A table mapping keys of arity field elements to a value, for example the
+// XOR of two bytes. A lookup gate outputs the value of the row keyed by its
+// inputs, and check_constraints accepts it only if its inputs and output form
+// a row of the table.
+// arity: the number of field elements in each key, which is the number of inputs of its lookup gates
+// rows: the value of every key in the table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupTable<F: Field> {
+    arity: usize,
+    rows: HashMap<Vec<F>, F>,
+}
+
+impl<F: Field> LookupTable<F> {
+    /*
+        Creates an empty table
+
+        ARGS:
+            arity: the number of field elements in each key
+     */
+    pub fn new(arity: usize) -> Self {
+        LookupTable { arity, rows: HashMap::new() }
+    }
+
+    /*
+        Creates the table of a function over small integers, with a row for every
+        key whose elements are each smaller than 2^num_bits. Panics if the keys 
+        take more than MAX_TABLE_BITS bits in total. 
+
+        ARGS:
+            arity: the number of elements in each key
+            num_bits: the bit width of each element of a key
+            f: the value of the row of each key, given as integers
+
+        RETURNS:
+            A table with 2^(arity * num_bits) rows
+     */
+    pub fn from_fn(arity: usize, num_bits: u32, f: impl Fn(&[u64]) -> u64) -> Self {
+        let key_bits = (num_bits as usize).saturating_mul(arity);
+        assert!(
+            key_bits <= MAX_TABLE_BITS as usize, 
+            "a table of {} keys of {} bits would have 2^{} rows, more than the 2^{} allowed", 
+            arity, num_bits, key_bits, MAX_TABLE_BITS,
+        );
+        let mut table = LookupTable::new(arity);
+        let mut key = vec![0u64; arity];
+        loop {
+            table.insert(key.iter().map(|&element| F::from(element)).collect(), F::from(f(&key)));
+
+            // step to the next key, with key[0] as the least significant element
+            match key.iter().position(|&element| element + 1 < 1 << num_bits) {
+                Some(position) => {
+                    key[position] += 1;
+                    key[..position].fill(0);
+                }
+                None => return table,
+            }
+        }
+    }
+
+    /*
+        Creates a range table, whose lookups output their input and only
+        accept values smaller than 2^num_bits
+
+        ARGS:
+            num_bits: the bit width of the range, at most MAX_TABLE_BITS
+     */
+    pub fn range(num_bits: u32) -> Self {
+        LookupTable::from_fn(1, num_bits, |key| key[0])
+    }
+
+    /*
+        Creates the table of the bitwise XOR of two values smaller than 2^num_bits
+
+        ARGS:
+            num_bits: the bit width of each operand, at most MAX_TABLE_BITS / 2
+     */
+    pub fn xor(num_bits: u32) -> Self {
+        LookupTable::from_fn(2, num_bits, |key| key[0] ^ key[1])
+    }
+
+    /*
+        Adds a row to the table, replacing the value of its key if already present
+
+        ARGS:
+            key: arity field elements
+            value: the value of the row
+     */
+    pub fn insert(&mut self, key: Vec<F>, value: F) {
+        assert_eq!(key.len(), self.arity, "a key of a table of arity {} has {} elements", self.arity, key.len());
+        self.rows.insert(key, value);
+    }
+
+    /*
+        RETURNS:
+            The number of field elements in each key
+     */
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /*
+        RETURNS:
+            The number of rows in the table
+     */
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /*
+        RETURNS:
+            Whether the table has no rows
+     */
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /*
+        RETURNS:
+            The value of the row with the given key, or None if there is no such row
+     */
+    pub fn get(&self, key: &[F]) -> Option<F> {
+        self.rows.get(key).copied()
+    }
+
+    /*
+        RETURNS:
+            Whether the table has a row with the given key and value
+     */
+    pub fn contains(&self, key: &[F], value: F) -> bool {
+        self.get(key) == Some(value)
+    }
+}
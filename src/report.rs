@@ -0,0 +1,252 @@
+use std::fmt;
+use crate::{builder::Derivation, field::Field};
+
+// Snapshot of a node taken while checking constraints
+// id: the id of the node
+// value: the value of the node, or None if it was unfilled
+// derivation: the method used to derive this nodes value
+// parents: the ids and values of the nodes directly used to derive this node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeReport<F: Field> {
+    pub id: usize,
+    pub value: Option<F>,
+    pub derivation: Derivation,
+    pub parents: Vec<(usize, Option<F>)>,
+}
+
+// An equality assertion whose two nodes hold different values
+// index: the position of the assertion in the order it was declared
+// left, right: snapshots of the two nodes of the assertion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionFailure<F: Field> {
+    pub index: usize,
+    pub left: NodeReport<F>,
+    pub right: NodeReport<F>,
+}
+
+// An equality assertion that could not be checked because at 
+// least one of its nodes is unfilled
+// index: the position of the assertion in the order it was declared
+// left_id, right_id: the ids of the two nodes of the assertion
+// unfilled_ids: the ids of the nodes among them without a value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnevaluableAssertion {
+    pub index: usize,
+    pub left_id: usize,
+    pub right_id: usize,
+    pub unfilled_ids: Vec<usize>,
+}
+
+// A lookup gate whose inputs and output do not form a row of its table, 
+// or which could not be checked because one of its nodes is unfilled
+// index: the position of the lookup gate among the lookups of the circuit
+// table: the index of the table of the gate
+// input_ids, output_id: the ids of the nodes of the gate
+// inputs, output: the values of the nodes, or None for unfilled nodes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupFailure<F: Field> {
+    pub index: usize,
+    pub table: usize,
+    pub input_ids: Vec<usize>,
+    pub output_id: usize,
+    pub inputs: Vec<Option<F>>,
+    pub output: Option<F>,
+}
+
+// Outcome of checking every equality assertion and lookup in a circuit. 
+// passed: the number of assertions that hold
+// failures: every assertion that does not hold, in declaration order
+// unevaluable: every assertion with an unfilled node, in declaration order
+// lookups_passed: the number of lookups whose nodes form a row of their table
+// lookup_failures: every other lookup, in the order of their indices
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintReport<F: Field> {
+    pub passed: usize,
+    pub failures: Vec<AssertionFailure<F>>,
+    pub unevaluable: Vec<UnevaluableAssertion>,
+    pub lookups_passed: usize,
+    pub lookup_failures: Vec<LookupFailure<F>>,
+}
+
+// A node left unfilled by a partial fill because its value depends on unset inputs
+// id: the id of the node
+// missing_input_ids: the ids of the unset inputs it is derived from, in increasing order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockedNode {
+    pub id: usize,
+    pub missing_input_ids: Vec<usize>,
+}
+
+// Outcome of filling the nodes computable from the inputs that are set
+// blocked: every constant or gate output left unfilled, in order of id
+// missing_input_ids: the ids of every unset input, in increasing order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialFill {
+    pub blocked: Vec<BlockedNode>,
+    pub missing_input_ids: Vec<usize>,
+}
+
+// Result of checking a single equality assertion
+pub(crate) enum AssertionOutcome<F: Field> {
+    Passed,
+    Failed(AssertionFailure<F>),
+    Unevaluable(UnevaluableAssertion),
+}
+
+impl<F: Field> AssertionOutcome<F> {
+    pub(crate) fn failed(&self) -> bool {
+        matches!(self, AssertionOutcome::Failed(_))
+    }
+}
+
+impl<F: Field> ConstraintReport<F> {
+    /*
+        Collects the outcomes of checking each assertion into a report
+
+        ARGS:
+            outcomes: the outcome of every assertion, in declaration order
+     */
+    pub(crate) fn from_outcomes(outcomes: impl IntoIterator<Item = AssertionOutcome<F>>) -> Self {
+        let mut report = ConstraintReport { 
+            passed: 0, 
+            failures: Vec::new(), 
+            unevaluable: Vec::new(), 
+            lookups_passed: 0, 
+            lookup_failures: Vec::new(),
+        };
+        for outcome in outcomes {
+            match outcome {
+                AssertionOutcome::Passed => report.passed += 1,
+                AssertionOutcome::Failed(failure) => report.failures.push(failure),
+                AssertionOutcome::Unevaluable(assertion) => report.unevaluable.push(assertion),
+            }
+        }
+        report
+    }
+
+    /*
+        Adds the outcomes of checking each lookup to a report
+
+        ARGS:
+            outcomes: None for each lookup that holds, and the failure of every 
+            other lookup, in the order of their indices
+     */
+    pub(crate) fn with_lookups(mut self, outcomes: impl IntoIterator<Item = Option<LookupFailure<F>>>) -> Self {
+        for outcome in outcomes {
+            match outcome {
+                None => self.lookups_passed += 1,
+                Some(failure) => self.lookup_failures.push(failure),
+            }
+        }
+        self
+    }
+
+    /*
+        RETURNS:
+            Whether every assertion and lookup was checked and holds
+     */
+    pub fn is_satisfied(&self) -> bool {
+        self.failures.is_empty() && self.unevaluable.is_empty() && self.lookup_failures.is_empty()
+    }
+
+    /*
+        RETURNS:
+            The number of assertions that do not hold
+     */
+    pub fn num_failed(&self) -> usize {
+        self.failures.len()
+    }
+
+    /*
+        RETURNS:
+            The number of assertions that could not be checked
+     */
+    pub fn num_unevaluable(&self) -> usize {
+        self.unevaluable.len()
+    }
+
+    /*
+        RETURNS:
+            The total number of assertions in the circuit
+     */
+    pub fn num_assertions(&self) -> usize {
+        self.passed + self.num_failed() + self.num_unevaluable()
+    }
+
+    /*
+        RETURNS:
+            The total number of lookups in the circuit
+     */
+    pub fn num_lookups(&self) -> usize {
+        self.lookups_passed + self.lookup_failures.len()
+    }
+}
+
+impl PartialFill {
+    /*
+        RETURNS:
+            Whether every node was filled
+     */
+    pub fn is_complete(&self) -> bool {
+        self.blocked.is_empty() && self.missing_input_ids.is_empty()
+    }
+}
+
+// Formats an optional value, printing unfilled values as such
+fn format_value<F: Field>(value: &Option<F>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "unfilled".to_string(),
+    }
+}
+
+impl<F: Field> fmt::Display for NodeReport<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Node {}: value {}, derivation {:?}", self.id, format_value(&self.value), self.derivation)?;
+        if !self.parents.is_empty() {
+            let parents: Vec<_> = self.parents.iter()
+                .map(|(id, value)| format!("{} = {}", id, format_value(value)))
+                .collect();
+            write!(f, ", parents [{}]", parents.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: Field> fmt::Display for ConstraintReport<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} assertions: {} passed, {} failed, {} unevaluable", 
+            self.num_assertions(), self.passed, self.num_failed(), self.num_unevaluable())?;
+        for failure in &self.failures {
+            writeln!(f, "Assertion {} failed", failure.index)?;
+            writeln!(f, "    {}", failure.left)?;
+            writeln!(f, "    {}", failure.right)?;
+        }
+        for assertion in &self.unevaluable {
+            writeln!(f, "Assertion {} between nodes {} and {} is unevaluable, unfilled nodes {:?}", 
+                assertion.index, assertion.left_id, assertion.right_id, assertion.unfilled_ids)?;
+        }
+        if self.num_lookups() > 0 {
+            writeln!(f, "{} lookups: {} passed, {} failed", self.num_lookups(), self.lookups_passed, self.lookup_failures.len())?;
+        }
+        for failure in &self.lookup_failures {
+            let inputs: Vec<_> = failure.input_ids.iter().zip(&failure.inputs)
+                .map(|(id, value)| format!("{} = {}", id, format_value(value)))
+                .collect();
+            writeln!(f, "Lookup {} into table {} failed, inputs [{}], output {} = {}", 
+                failure.index, failure.table, inputs.join(", "), failure.output_id, format_value(&failure.output))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PartialFill {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} nodes blocked on {} unset inputs {:?}", 
+            self.blocked.len(), self.missing_input_ids.len(), self.missing_input_ids)?;
+        for node in &self.blocked {
+            writeln!(f, "Node {} depends on unset inputs {:?}", node.id, node.missing_input_ids)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use std::{cell::UnsafeCell, fmt, sync::atomic::{AtomicU64, Ordering}};
+use crate::field::Field;
+
+// Contiguous storage for the values of every node in a circuit, indexed by node id.
+// values: the value of each node, meaningful only while its validity bit is set
+// filled: a bitmap with one bit per node, set once the node holds a value
+//
+// Values are written through a shared reference so that gates can be evaluated
+// in parallel. This is sound as long as no two threads access the same node
+// while it is being written, which the schedulers guarantee: every node is the
+// output of exactly one gate, and a gate only reads nodes whose gates have
+// completed (the barrier between levels, or the dependency counts in dataflow).
+pub(crate) struct ValueStore<F: Field> {
+    values: Vec<UnsafeCell<F>>,
+    filled: Vec<AtomicU64>,
+}
+
+// SAFETY: shared access only writes values under the contract of ValueStore::write
+unsafe impl<F: Field> Sync for ValueStore<F> {}
+
+impl<F: Field> Default for ValueStore<F> {
+    fn default() -> Self {
+        ValueStore { values: Vec::new(), filled: Vec::new() }
+    }
+}
+
+impl<F: Field> fmt::Debug for ValueStore<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries((0..self.len()).map(|id| self.get(id))).finish()
+    }
+}
+
+impl<F: Field> ValueStore<F> {
+    /*
+        Creates storage for a number of unfilled nodes
+
+        ARGS:
+            len: the number of nodes
+     */
+    pub(crate) fn new(len: usize) -> Self {
+        let mut store = ValueStore::default();
+        store.extend(len);
+        store
+    }
+
+    /*
+        RETURNS:
+            The number of nodes in the store
+     */
+    pub(crate) fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /*
+        Appends unfilled nodes to the store
+
+        ARGS:
+            count: the number of nodes to add
+     */
+    pub(crate) fn extend(&mut self, count: usize) {
+        let len = self.values.len() + count;
+        self.values.resize_with(len, || UnsafeCell::new(F::default()));
+        self.filled.resize_with(len.div_ceil(64), || AtomicU64::new(0));
+    }
+
+    /*
+        Reads the value of a node
+
+        ARGS:
+            id: the id of the node
+
+        RETURNS:
+            The value of the node, or None if it is unfilled
+     */
+    pub(crate) fn get(&self, id: usize) -> Option<F> {
+        if self.filled[id / 64].load(Ordering::Acquire) & (1 << (id % 64)) == 0 {
+            return None;
+        }
+        // SAFETY: the value was fully written before its bit was set, and is
+        // not written again while it may be read, by the contract of write
+        Some(unsafe { *self.values[id].get() })
+    }
+
+    /*
+        Sets or clears the value of a node through a shared reference
+
+        ARGS:
+            id: the id of the node
+            value: the new value, or None to mark the node unfilled
+
+        SAFETY:
+            No other thread may read or write the node during the call
+     */
+    pub(crate) unsafe fn write(&self, id: usize, value: Option<F>) {
+        match value {
+            Some(value) => {
+                *self.values[id].get() = value;
+                self.filled[id / 64].fetch_or(1 << (id % 64), Ordering::Release);
+            }
+            None => self.clear(id),
+        }
+    }
+
+    /*
+        Sets or clears the value of a node
+
+        ARGS:
+            id: the id of the node
+            value: the new value, or None to mark the node unfilled
+     */
+    pub(crate) fn set(&mut self, id: usize, value: Option<F>) {
+        // SAFETY: the exclusive borrow rules out any concurrent access
+        unsafe { self.write(id, value) }
+    }
+
+    /*
+        Marks a node as unfilled. Only the validity bit is touched,
+        so this may run concurrently with reads of other nodes.
+
+        ARGS:
+            id: the id of the node
+     */
+    pub(crate) fn clear(&self, id: usize) {
+        self.filled[id / 64].fetch_and(!(1 << (id % 64)), Ordering::Release);
+    }
+}
+
+// Storage for the values of every node across a batch of witnesses, indexed by node id.
+// The values of one node for every witness are stored next to each other, as the
+// row of that node, so a gate is evaluated on the whole batch by one loop over
+// contiguous rows. There is no validity bitmap: every row is written before it is
+// read, since inputs and constants are filled in before any gate is evaluated.
+// values: the row of each node, one after another
+// batch_size: the number of witnesses, which is the length of each row
+pub(crate) struct BatchStore<F: Field> {
+    values: Vec<UnsafeCell<F>>,
+    batch_size: usize,
+}
+
+// SAFETY: shared access only writes rows under the contract of BatchStore::row_mut
+unsafe impl<F: Field> Sync for BatchStore<F> {}
+
+impl<F: Field> BatchStore<F> {
+    /*
+        Creates storage for a batch of witnesses
+
+        ARGS:
+            num_nodes: the number of nodes in the circuit
+            batch_size: the number of witnesses
+     */
+    pub(crate) fn new(num_nodes: usize, batch_size: usize) -> Self {
+        let mut values = Vec::new();
+        values.resize_with(num_nodes * batch_size, || UnsafeCell::new(F::default()));
+        BatchStore { values, batch_size }
+    }
+
+    /*
+        RETURNS:
+            The cells holding the values of a node across the batch
+     */
+    fn cells(&self, id: usize) -> &[UnsafeCell<F>] {
+        &self.values[id * self.batch_size..(id + 1) * self.batch_size]
+    }
+
+    /*
+        Reads the values of a node across the batch
+
+        ARGS:
+            id: the id of the node
+
+        RETURNS:
+            The value of the node in every witness
+     */
+    pub(crate) fn row(&self, id: usize) -> &[F] {
+        // SAFETY: UnsafeCell<F> has the layout of F, and the row is not written 
+        // while it may be read, by the contract of row_mut
+        unsafe { std::slice::from_raw_parts(UnsafeCell::raw_get(self.cells(id).as_ptr()), self.batch_size) }
+    }
+
+    /*
+        Gives mutable access to the values of a node through a shared reference
+
+        ARGS:
+            id: the id of the node
+
+        SAFETY:
+            No other reference to the row may exist while the returned slice is alive
+     */
+    #[allow(clippy::mut_from_ref)]
+    pub(crate) unsafe fn row_mut(&self, id: usize) -> &mut [F] {
+        std::slice::from_raw_parts_mut(UnsafeCell::raw_get(self.cells(id).as_ptr()), self.batch_size)
+    }
+
+    /*
+        Gives mutable access to the values of a node
+
+        ARGS:
+            id: the id of the node
+     */
+    pub(crate) fn row_mut_exclusive(&mut self, id: usize) -> &mut [F] {
+        // SAFETY: the exclusive borrow rules out any other reference to the row
+        unsafe { self.row_mut(id) }
+    }
+}
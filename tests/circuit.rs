@@ -0,0 +1,218 @@
+use takehome::builder::*;
+use takehome::circuit::*;
+use takehome::error::*;
+use takehome::field::{Field, Fp};
+use rand::{rngs::StdRng, SeedableRng, Rng};
+use std::{sync::Arc, thread};
+
+// the Goldilocks prime 2^64 - 2^32 + 1
+type F = Fp<0xffff_ffff_0000_0001>;
+
+// f(x, y) = x * y + x + 5, asserting that a hinted inverse of y is correct
+fn example_circuit() -> (Circuit<F>, Node, Node) {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let y = builder.init();
+    let xy = builder.mul(x, y);
+    let five = builder.constant(F::from(5));
+    let xy_plus_5 = builder.add(xy, five);
+    let f = builder.add(xy_plus_5, x);
+
+    let y_inv = builder.hint(&[y], |val: &[F]| val[0].inverse().ok_or(HintError::new("zero has no inverse")));
+    let product = builder.mul(y, y_inv);
+    let one = builder.constant(F::one());
+    builder.assert_equal(product, one);
+
+    (builder.finalize(), f, y_inv)
+}
+
+#[test]
+fn test_evaluate() {
+    let (circuit, f, y_inv) = example_circuit();
+    assert_eq!(circuit.inputs().len(), 2);
+
+    let mut rng = StdRng::from_seed([0u8; 32]);
+    for _ in 0..10 {
+        let x_val: u32 = rng.gen();
+        let y_val: u32 = rng.gen_range(1..u32::MAX);
+
+        let witness = circuit.evaluate(&[F::from(x_val as u64), F::from(y_val as u64)]).unwrap();
+        assert_eq!(witness.get(f), F::from(x_val as u64 * y_val as u64 + x_val as u64 + 5));
+        assert_eq!(witness.get(y_inv) * F::from(y_val as u64), F::one());
+        assert!(circuit.constraint_report(&witness).is_satisfied());
+    }
+}
+
+#[test]
+fn test_evaluate_errors() {
+    let (circuit, f, y_inv) = example_circuit();
+
+    assert_eq!(circuit.evaluate(&[F::from(1)]).unwrap_err(), EvalError::InputCount { expected: 2, given: 1 });
+
+    // a failing hint is reported, and nothing is left behind for the next evaluation
+    match circuit.evaluate(&[F::from(3), F::from(0)]) {
+        Err(EvalError::Hint { output_ids, .. }) => assert_eq!(output_ids, vec![y_inv.id]),
+        result => panic!("unexpected result {:?}", result),
+    }
+    let witness = circuit.evaluate(&[F::from(3), F::from(4)]).unwrap();
+    assert_eq!(witness.get(f), F::from(20));
+}
+
+#[test]
+fn test_builder_values_not_carried_over() {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let y = builder.init();
+    let sum = builder.add(x, y);
+
+    builder.set(x, F::from(1));
+    builder.set(y, F::from(2));
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(sum), F::from(3));
+
+    let circuit = builder.finalize();
+    let witness = circuit.evaluate(&[F::from(10), F::from(20)]).unwrap();
+    assert_eq!(witness.get(x), F::from(10));
+    assert_eq!(witness.get(sum), F::from(30));
+    assert_eq!(circuit.derivation(sum), Derivation::Add);
+    assert_eq!(circuit.parents(sum), &[x.id, y.id]);
+}
+
+fn square_repeatedly(x: F, times: usize) -> F {
+    (0..times).fold(x, |acc, _| acc * acc)
+}
+
+#[test]
+fn test_concurrent_evaluation() {
+    let mut builder = Builder::<F>::new();
+    builder.set_eval_strategy(EvalStrategy::Dataflow);
+
+    // a chain of squarings, with the result checked against a hint
+    let x = builder.init();
+    let mut node = x;
+    for _ in 0..500 {
+        node = builder.mul(node, node);
+    }
+    let expected = builder.hint(&[x], |val: &[F]| Ok(square_repeatedly(val[0], 500)));
+    builder.assert_equal(node, expected);
+    let circuit = Arc::new(builder.finalize());
+    assert_eq!(circuit.eval_strategy(), EvalStrategy::Dataflow);
+
+    let handles: Vec<_> = (0..8u64).map(|i| {
+        let circuit = Arc::clone(&circuit);
+        thread::spawn(move || {
+            let witness = circuit.evaluate(&[F::from(i + 2)]).unwrap();
+            assert!(circuit.constraint_report(&witness).is_satisfied());
+            witness.get(node)
+        })
+    }).collect();
+
+    let results: Vec<F> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+    for (i, result) in results.into_iter().enumerate() {
+        assert_eq!(result, square_repeatedly(F::from(i as u64 + 2), 500));
+    }
+}
+
+// Checks that every row of a batch evaluates to the same witness, or the same error, as evaluate
+fn check_batch(circuit: &Circuit<F>, nodes: &[Node], rows: &[Vec<F>]) {
+    let results = circuit.evaluate_batch(rows);
+    assert_eq!(results.len(), rows.len());
+    for (row, result) in rows.iter().zip(results) {
+        match (circuit.evaluate(row), result) {
+            (Ok(expected), Ok(witness)) => {
+                for &node in nodes {
+                    assert_eq!(witness.get(node), expected.get(node));
+                }
+            }
+            (expected, result) => assert_eq!(expected.unwrap_err(), result.unwrap_err()),
+        }
+    }
+}
+
+#[test]
+fn test_evaluate_batch() {
+    let (circuit, f, y_inv) = example_circuit();
+    let mut rng = StdRng::from_seed([1u8; 32]);
+
+    // every fourth row has y = 0, so that the inverse hint fails, 
+    // and one row is missing an input
+    let mut rows: Vec<Vec<F>> = (0..64).map(|i| {
+        let y = if i % 4 == 0 { 0 } else { rng.gen() };
+        vec![F::from(rng.gen::<u64>()), F::from(y)]
+    }).collect();
+    rows[10].pop();
+
+    let results = circuit.evaluate_batch(&rows);
+    assert_eq!(results[10].as_ref().unwrap_err(), &EvalError::InputCount { expected: 2, given: 1 });
+    assert!(matches!(results[0], Err(EvalError::Hint { .. })));
+    assert!(results[1].is_ok());
+    check_batch(&circuit, &[f, y_inv], &rows);
+
+    assert!(circuit.evaluate_batch::<Vec<F>>(&[]).is_empty());
+}
+
+#[test]
+fn test_evaluate_batch_linear_gates() {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let y = builder.init();
+    let difference = builder.sub(x, y);
+    let negated = builder.neg(difference);
+    let scaled = builder.mul_const(negated, F::from(3));
+    let shifted = builder.add_const(scaled, F::from(11));
+    let combination = builder.linear_combination(&[(F::from(2), x), (F::from(5), shifted), (-F::one(), y)], F::from(4));
+    let circuit = builder.finalize();
+
+    let mut rng = StdRng::seed_from_u64(5);
+    let rows: Vec<Vec<F>> = (0..20).map(|_| vec![F::from(rng.gen::<u64>()), F::from(rng.gen::<u64>())]).collect();
+    check_batch(&circuit, &[x, y, difference, negated, scaled, shifted, combination], &rows);
+
+    let witness = circuit.evaluate(&[F::from(2), F::from(9)]).unwrap();
+    assert_eq!(witness.get(shifted), F::from(32));
+    assert_eq!(witness.get(combination), F::from(159));
+}
+
+#[test]
+fn test_witness_foreign_node() {
+    let (circuit, f, _) = example_circuit();
+    let witness = circuit.evaluate(&[F::from(3), F::from(4)]).unwrap();
+    assert_eq!(witness.try_get(f), Ok(F::from(20)));
+
+    let mut other_builder = Builder::<F>::new();
+    let in_range = other_builder.init();
+    let past_end = other_builder.batch_init(20)[19];
+    assert_eq!(witness.try_get(in_range), Err(BuilderError::ForeignNode { id: in_range.id }));
+    assert_eq!(witness.try_get(past_end), Err(BuilderError::ForeignNode { id: past_end.id }));
+}
+
+#[test]
+fn test_evaluate_batch_random_circuit() {
+    let mut rng = StdRng::seed_from_u64(12);
+    let mut builder = Builder::<F>::new();
+    let mut nodes = builder.batch_init(16);
+    nodes.extend(builder.batch_constant(&[F::from(3), F::from(7)]));
+
+    // hints dividing by a node fail whenever it is zero, which some rows force
+    for _ in 0..2000 {
+        let a = nodes[rng.gen_range(0..nodes.len())];
+        let b = nodes[rng.gen_range(0..nodes.len())];
+        let node = match rng.gen_range(0..4) {
+            0 => builder.add(a, b),
+            1 => builder.mul(a, b),
+            2 => builder.hint(&[a, b], |val: &[F]| Ok(val[0] * val[1] + F::one())),
+            _ => builder.hint(&[a, b], |val: &[F]| {
+                val[1].inverse().map(|inverse| val[0] * inverse).ok_or(HintError::new("division by zero"))
+            }),
+        };
+        nodes.push(node);
+    }
+    let circuit = builder.finalize();
+
+    let rows: Vec<Vec<F>> = (0..50).map(|i| {
+        (0..16).map(|input| if i % 7 == 0 && input == i % 16 { F::zero() } else { F::from(rng.gen::<u64>()) }).collect()
+    }).collect();
+    check_batch(&circuit, &nodes, &rows);
+
+    let failed = circuit.evaluate_batch(&rows).iter().filter(|result| result.is_err()).count();
+    assert!(failed > 0 && failed < rows.len());
+}
@@ -0,0 +1,286 @@
+use takehome::builder::*;
+use takehome::error::*;
+use takehome::field::*;
+use rand::{rngs::StdRng, SeedableRng, Rng};
+use std::cmp::Ordering;
+
+// Naive arbitrary precision unsigned integer, used as an independent
+// reference for the field implementations. Little-endian u32 limbs
+// with no trailing zero limbs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct BigUint(Vec<u32>);
+
+impl BigUint {
+    fn from_limbs(limbs: &[u64]) -> Self {
+        BigUint(limbs.iter().flat_map(|&limb| [limb as u32, (limb >> 32) as u32]).collect()).normalize()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        BigUint(bytes.chunks(4).map(|chunk| {
+            chunk.iter().rev().fold(0u32, |acc, &byte| (acc << 8) | byte as u32)
+        }).collect()).normalize()
+    }
+
+    fn to_bytes(&self, len: usize) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self.0.iter().flat_map(|limb| limb.to_le_bytes()).collect();
+        assert!(bytes[len.min(bytes.len())..].iter().all(|&byte| byte == 0));
+        bytes.resize(len, 0);
+        bytes
+    }
+
+    fn normalize(mut self) -> Self {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+        self
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self.0.get(i / 32).is_some_and(|limb| (limb >> (i % 32)) & 1 == 1)
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.len().cmp(&other.0.len()).then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        let mut out = Vec::new();
+        let mut carry = 0u64;
+        for i in 0..self.0.len().max(other.0.len()) {
+            let sum = *self.0.get(i).unwrap_or(&0) as u64 + *other.0.get(i).unwrap_or(&0) as u64 + carry;
+            out.push(sum as u32);
+            carry = sum >> 32;
+        }
+        out.push(carry as u32);
+        BigUint(out).normalize()
+    }
+
+    // requires self >= other
+    fn sub(&self, other: &Self) -> Self {
+        let mut out = Vec::new();
+        let mut borrow = 0i64;
+        for i in 0..self.0.len() {
+            let mut diff = self.0[i] as i64 - *other.0.get(i).unwrap_or(&0) as i64 - borrow;
+            borrow = 0;
+            if diff < 0 {
+                diff += 1 << 32;
+                borrow = 1;
+            }
+            out.push(diff as u32);
+        }
+        assert_eq!(borrow, 0);
+        BigUint(out).normalize()
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        let mut out = vec![0u64; self.0.len() + other.0.len() + 1];
+        for (i, &a) in self.0.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.0.iter().enumerate() {
+                let current = out[i + j] + a as u64 * b as u64 + carry;
+                out[i + j] = current & 0xffff_ffff;
+                carry = current >> 32;
+            }
+            out[i + other.0.len()] += carry;
+        }
+        BigUint(out.into_iter().map(|limb| limb as u32).collect()).normalize()
+    }
+
+    // schoolbook binary long division, keeping only the remainder
+    fn rem(&self, modulus: &Self) -> Self {
+        let mut remainder = BigUint(Vec::new());
+        for i in (0..self.0.len() * 32).rev() {
+            remainder = remainder.add(&remainder);
+            if self.bit(i) {
+                remainder = remainder.add(&BigUint(vec![1]));
+            }
+            if remainder.cmp(modulus) != Ordering::Less {
+                remainder = remainder.sub(modulus);
+            }
+        }
+        remainder
+    }
+}
+
+fn to_big<F: Field>(x: F) -> BigUint {
+    BigUint::from_bytes(&x.to_canonical_bytes())
+}
+
+fn from_big<F: Field>(x: &BigUint) -> F {
+    F::from_canonical_bytes(&x.to_bytes(F::NUM_BYTES)).unwrap()
+}
+
+// Elements near zero and near the modulus, the usual places for carry
+// and reduction bugs, together with random elements of the field.
+fn sample_elements<F: Field>(modulus: &BigUint, rng: &mut StdRng, num_random: usize) -> Vec<F> {
+    let one = BigUint(vec![1]);
+    let mut elements = Vec::new();
+    let mut low = BigUint(Vec::new());
+    let mut high = modulus.sub(&one);
+    for _ in 0..8 {
+        elements.push(from_big(&low));
+        elements.push(from_big(&high));
+        low = low.add(&one);
+        high = high.sub(&one);
+    }
+
+    for power in [31, 32, 63, 64, 127, 128, 253] {
+        let mut limbs = vec![0u64; power / 64 + 1];
+        limbs[power / 64] = 1 << (power % 64);
+        let value = BigUint::from_limbs(&limbs);
+        elements.push(from_big(&value.rem(modulus)));
+        elements.push(from_big(&value.sub(&one).rem(modulus)));
+    }
+
+    for _ in 0..num_random {
+        let bytes: Vec<u8> = (0..F::NUM_BYTES).map(|_| rng.gen()).collect();
+        elements.push(from_big(&BigUint::from_bytes(&bytes).rem(modulus)));
+    }
+    elements
+}
+
+fn check_field<F: Field>(modulus: BigUint) {
+    let mut rng = StdRng::from_seed([0u8; 32]);
+    let elements: Vec<F> = sample_elements(&modulus, &mut rng, 24);
+    let one = BigUint(vec![1]);
+
+    assert_eq!(to_big(F::zero()), BigUint(Vec::new()));
+    assert_eq!(to_big(F::one()), one);
+
+    for &a in &elements {
+        let a_big = to_big(a);
+
+        assert_eq!(to_big(-a), modulus.sub(&a_big).rem(&modulus));
+        assert_eq!(F::from_canonical_bytes(&a.to_canonical_bytes()), Some(a));
+
+        match a.inverse() {
+            None => assert!(a.is_zero()),
+            Some(inverse) => assert_eq!(a_big.mul(&to_big(inverse)).rem(&modulus), one),
+        }
+
+        for &b in &elements {
+            let b_big = to_big(b);
+            assert_eq!(to_big(a + b), a_big.add(&b_big).rem(&modulus));
+            assert_eq!(to_big(a - b), a_big.add(&modulus).sub(&b_big).rem(&modulus));
+            assert_eq!(to_big(a * b), a_big.mul(&b_big).rem(&modulus));
+        }
+    }
+
+    // conversion from integers reduces modulo p
+    for value in [0, 1, u32::MAX as u64, u64::MAX, u64::MAX - 1].into_iter().chain((0..64).map(|_| rng.gen())) {
+        assert_eq!(to_big(F::from(value)), BigUint::from_limbs(&[value]).rem(&modulus));
+    }
+
+    // exponentiation agrees with repeated multiplication
+    let base = elements[elements.len() - 1];
+    let mut expected = F::one();
+    for exp in 0..40 {
+        assert_eq!(base.pow(exp), expected);
+        expected = expected * base;
+    }
+
+    // non-canonical encodings are rejected
+    assert_eq!(F::from_canonical_bytes(&modulus.to_bytes(F::NUM_BYTES)), None);
+    assert_eq!(F::from_canonical_bytes(&vec![0xff; F::NUM_BYTES]), None);
+    assert_eq!(F::from_canonical_bytes(&vec![0; F::NUM_BYTES + 1]), None);
+    assert_eq!(F::from_canonical_bytes(&modulus.sub(&one).to_bytes(F::NUM_BYTES)), Some(-F::one()));
+}
+
+// Exhaustively checks every pair drawn from a window just above zero
+// and a window just below the modulus.
+fn check_field_windows<F: Field>(modulus: BigUint) {
+    let one = BigUint(vec![1]);
+    let mut window = Vec::new();
+    let mut high = modulus.sub(&one);
+    for i in 0..64u64 {
+        window.push(F::from(i));
+        window.push(from_big(&high));
+        high = high.sub(&one);
+    }
+
+    for &a in &window {
+        for &b in &window {
+            let (a_big, b_big) = (to_big(a), to_big(b));
+            assert_eq!(to_big(a + b), a_big.add(&b_big).rem(&modulus));
+            assert_eq!(to_big(a - b), a_big.add(&modulus).sub(&b_big).rem(&modulus));
+            assert_eq!(to_big(a * b), a_big.mul(&b_big).rem(&modulus));
+        }
+    }
+}
+
+fn lambda_inverse<F: Field>(val: &[F]) -> Result<F, HintError> {
+    val[0].inverse().ok_or(HintError::new("zero has no inverse"))
+}
+
+// f(x) = x^2 + x + 5, together with a hinted inverse of x
+async fn check_builder<F: Field>() {
+    let mut builder = Builder::<F>::new();
+
+    let x = builder.init();
+    let x_squared = builder.mul(x, x);
+    let five = builder.constant(F::from(5));
+    let x_squared_plus_5 = builder.add(x_squared, five);
+    let y = builder.add(x_squared_plus_5, x);
+
+    let x_inv = builder.hint(std::slice::from_ref(&x), lambda_inverse::<F>);
+    let product = builder.mul(x, x_inv);
+    let one = builder.constant(F::one());
+    builder.assert_equal(product, one);
+
+    // x = -2 gives 4 - 2 + 5 = 7
+    builder.set(x, -F::from(2));
+    builder.fill_nodes().unwrap();
+
+    assert_eq!(builder.get(y), F::from(7));
+    assert_eq!(builder.get(x_inv) * F::from(2), -F::one());
+    assert!(builder.check_constraints().await);
+}
+
+#[test]
+fn test_fp_arithmetic() {
+    check_field::<Fp<0xffff_ffff_ffff_ffc5>>(BigUint::from_limbs(&[0xffff_ffff_ffff_ffc5]));
+    check_field_windows::<Fp<1_000_000_007>>(BigUint::from_limbs(&[1_000_000_007]));
+}
+
+#[test]
+fn test_goldilocks_arithmetic() {
+    check_field::<Goldilocks>(BigUint::from_limbs(&[Goldilocks::MODULUS]));
+    check_field_windows::<Goldilocks>(BigUint::from_limbs(&[Goldilocks::MODULUS]));
+}
+
+#[test]
+fn test_babybear_arithmetic() {
+    check_field::<BabyBear>(BigUint::from_limbs(&[BabyBear::MODULUS as u64]));
+    check_field_windows::<BabyBear>(BigUint::from_limbs(&[BabyBear::MODULUS as u64]));
+}
+
+#[test]
+fn test_mersenne31_arithmetic() {
+    check_field::<Mersenne31>(BigUint::from_limbs(&[Mersenne31::MODULUS as u64]));
+    check_field_windows::<Mersenne31>(BigUint::from_limbs(&[Mersenne31::MODULUS as u64]));
+}
+
+#[test]
+fn test_bn254_arithmetic() {
+    check_field::<Bn254Scalar>(BigUint::from_limbs(&Bn254Scalar::MODULUS));
+    check_field_windows::<Bn254Scalar>(BigUint::from_limbs(&Bn254Scalar::MODULUS));
+}
+
+#[test]
+fn test_bn254_display() {
+    assert_eq!(format!("{}", Bn254Scalar::from(0)), "0");
+    assert_eq!(format!("{}", Bn254Scalar::from(u64::MAX)), "18446744073709551615");
+    assert_eq!(
+        format!("{}", -Bn254Scalar::one()),
+        "21888242871839275222246405745257275088548364400416034343698204186575808495616"
+    );
+}
+
+#[tokio::test]
+async fn test_fields_in_builder() {
+    check_builder::<Fp<0xffff_ffff_ffff_ffc5>>().await;
+    check_builder::<Goldilocks>().await;
+    check_builder::<BabyBear>().await;
+    check_builder::<Mersenne31>().await;
+    check_builder::<Bn254Scalar>().await;
+}
@@ -0,0 +1,879 @@
+use takehome::builder::*;
+use takehome::error::*;
+use takehome::field::{BabyBear, Bn254Scalar, Field, Fp, Goldilocks, Mersenne31};
+use takehome::gadgets::merkle::{CircuitHash, MerkleTree};
+use takehome::gadgets::mimc::MimcParams;
+use takehome::gadgets::poseidon::PoseidonParams;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{collections::VecDeque, iter};
+
+// the Goldilocks prime 2^64 - 2^32 + 1
+type F = Fp<0xffff_ffff_0000_0001>;
+
+fn from_bool(value: bool) -> F {
+    F::from(value as u64)
+}
+
+#[tokio::test]
+async fn test_boolean_gadgets() {
+    let mut builder = Builder::<F>::new();
+    let a = builder.init();
+    let b = builder.init();
+    let x = builder.init();
+    let y = builder.init();
+    builder.assert_bool(a);
+    builder.assert_bool(b);
+
+    let and = builder.and(a, b);
+    let or = builder.or(a, b);
+    let xor = builder.xor(a, b);
+    let not = builder.not(a);
+    let select = builder.select(a, x, y);
+
+    // outputs of boolean gadgets on boolean inputs are boolean themselves
+    for node in [and, or, xor, not] {
+        builder.assert_bool(node);
+    }
+
+    let mut rng = StdRng::seed_from_u64(18);
+    for _ in 0..100 {
+        let (a_val, b_val): (bool, bool) = (rng.gen(), rng.gen());
+        let (x_val, y_val) = (F::from(rng.gen::<u64>()), F::from(rng.gen::<u64>()));
+        builder.batch_set(&[a, b, x, y], &[from_bool(a_val), from_bool(b_val), x_val, y_val]);
+        builder.fill_nodes().unwrap();
+
+        assert_eq!(builder.get(and), from_bool(a_val && b_val));
+        assert_eq!(builder.get(or), from_bool(a_val || b_val));
+        assert_eq!(builder.get(xor), from_bool(a_val ^ b_val));
+        assert_eq!(builder.get(not), from_bool(!a_val));
+        assert_eq!(builder.get(select), if a_val { x_val } else { y_val });
+        assert!(builder.check_constraints().await);
+    }
+}
+
+#[tokio::test]
+async fn test_assert_bool_rejects_non_boolean() {
+    let mut builder = Builder::<F>::new();
+    let b = builder.init();
+    builder.assert_bool(b);
+
+    let mut rng = StdRng::seed_from_u64(19);
+    for _ in 0..20 {
+        let value = F::from(rng.gen_range(2..u64::MAX));
+        builder.set(b, value);
+        builder.fill_nodes().unwrap();
+        assert!(!builder.check_constraints().await);
+    }
+    for value in [F::zero(), F::one()] {
+        builder.set(b, value);
+        builder.fill_nodes().unwrap();
+        assert!(builder.check_constraints().await);
+    }
+}
+
+#[test]
+fn test_boolean_gadgets_random_expressions() {
+    // random formulas over a few boolean inputs, compared with plain Rust logic
+    let mut rng = StdRng::seed_from_u64(20);
+    let mut builder = Builder::<F>::new();
+    let inputs = builder.batch_init(4);
+    let values: Vec<bool> = (0..inputs.len()).map(|_| rng.gen()).collect();
+
+    let mut nodes = inputs.clone();
+    let mut expected = values.clone();
+    for _ in 0..200 {
+        let i = rng.gen_range(0..nodes.len());
+        let j = rng.gen_range(0..nodes.len());
+        let k = rng.gen_range(0..nodes.len());
+        let (node, value) = match rng.gen_range(0..5) {
+            0 => (builder.and(nodes[i], nodes[j]), expected[i] && expected[j]),
+            1 => (builder.or(nodes[i], nodes[j]), expected[i] || expected[j]),
+            2 => (builder.xor(nodes[i], nodes[j]), expected[i] ^ expected[j]),
+            3 => (builder.not(nodes[i]), !expected[i]),
+            _ => (builder.select(nodes[i], nodes[j], nodes[k]), if expected[i] { expected[j] } else { expected[k] }),
+        };
+        nodes.push(node);
+        expected.push(value);
+    }
+
+    builder.batch_set(&inputs, &values.iter().map(|&value| from_bool(value)).collect::<Vec<_>>());
+    builder.fill_nodes().unwrap();
+    for (&node, &value) in nodes.iter().zip(&expected) {
+        assert_eq!(builder.get(node), from_bool(value));
+    }
+}
+
+#[tokio::test]
+async fn test_to_bits() {
+    let mut rng = StdRng::seed_from_u64(21);
+    for num_bits in [1, 8, 32, 63] {
+        let mut builder = Builder::<F>::new();
+        let x = builder.init();
+        let bits = builder.to_bits(x, num_bits);
+        assert_eq!(bits.len(), num_bits);
+
+        for _ in 0..20 {
+            let value = rng.gen::<u64>() >> (64 - num_bits);
+            builder.set(x, F::from(value));
+            builder.fill_nodes().unwrap();
+            for (i, &bit) in bits.iter().enumerate() {
+                assert_eq!(builder.get(bit), F::from((value >> i) & 1));
+            }
+            assert!(builder.check_constraints().await);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_assert_range() {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    builder.assert_range(x, 8);
+
+    for (value, in_range) in [(0, true), (255, true), (256, false), (1000, false)] {
+        builder.set(x, F::from(value));
+        // values out of range fill normally, and fail the recomposition assertion
+        builder.fill_nodes().unwrap();
+        assert_eq!(builder.check_constraints().await, in_range);
+    }
+
+    // the wrapped value -1 = p - 1 is not small either
+    builder.set(x, -F::one());
+    builder.fill_nodes().unwrap();
+    assert!(!builder.check_constraints().await);
+}
+
+#[tokio::test]
+async fn test_bit_decomposition_costs() {
+    for num_bits in [1, 8, 16, 32, 63] {
+        let mut builder = Builder::<F>::new();
+        let x = builder.init();
+        builder.assert_range(x, num_bits);
+        builder.set(x, F::one());
+        builder.fill_nodes().unwrap();
+        assert_eq!(builder.constraint_report().await.num_assertions(), num_bits + 1);
+        assert_eq!(builder.finalize().num_gates(), num_bits + 2);
+    }
+}
+
+#[test]
+fn test_to_bits_width() {
+    assert_eq!(Builder::<F>::max_bits(), 63);
+    assert_eq!(Builder::<Goldilocks>::max_bits(), 63);
+    assert_eq!(Builder::<BabyBear>::max_bits(), 30);
+    assert_eq!(Builder::<Mersenne31>::max_bits(), 30);
+    assert_eq!(Builder::<Bn254Scalar>::max_bits(), 253);
+
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    assert_eq!(builder.try_to_bits(x, 64), Err(BuilderError::TooManyBits { bits: 64, max_bits: 63 }));
+    assert_eq!(builder.try_assert_range(x, 100), Err(BuilderError::TooManyBits { bits: 100, max_bits: 63 }));
+
+    // decompositions are not limited to 64 bits in larger fields
+    let mut builder = Builder::<Bn254Scalar>::new();
+    let x = builder.init();
+    let bits = builder.to_bits(x, 100);
+    builder.set(x, Bn254Scalar::from(1 << 40) * Bn254Scalar::from(1 << 40) * Bn254Scalar::from(3));
+    builder.fill_nodes().unwrap();
+    let ones: Vec<usize> = (0..100).filter(|&i| builder.get(bits[i]) == Bn254Scalar::one()).collect();
+    assert_eq!(ones, vec![80, 81]);
+}
+
+#[tokio::test]
+async fn test_is_zero_and_is_equal() {
+    let mut builder = Builder::<F>::new();
+    let a = builder.init();
+    let b = builder.init();
+    let a_zero = builder.is_zero(a);
+    let equal = builder.is_equal(a, b);
+
+    let mut rng = StdRng::seed_from_u64(22);
+    for _ in 0..50 {
+        let a_val = if rng.gen_bool(0.3) { F::zero() } else { F::from(rng.gen::<u64>()) };
+        let b_val = if rng.gen_bool(0.3) { a_val } else { F::from(rng.gen::<u64>()) };
+        builder.batch_set(&[a, b], &[a_val, b_val]);
+        builder.fill_nodes().unwrap();
+        assert_eq!(builder.get(a_zero), from_bool(a_val == F::zero()));
+        assert_eq!(builder.get(equal), from_bool(a_val == b_val));
+        assert!(builder.check_constraints().await);
+    }
+}
+
+#[test]
+fn test_is_zero_shares_zero_constant() {
+    let mut builder = Builder::<F>::new();
+    let inputs = builder.batch_init(6);
+    for pair in inputs.chunks(2) {
+        builder.is_equal(pair[0], pair[1]);
+    }
+
+    // the first call creates the zero constant, and every call adds its own 5 nodes
+    let circuit = builder.finalize();
+    assert_eq!(circuit.num_nodes(), 6 + 1 + 3 * 5);
+}
+
+// The hint node among the parents of the product x * inv built by is_zero
+fn inverse_hint(builder: &Builder<F>, out: Node) -> Node {
+    let product = builder.node(builder.parents(out)[0]).unwrap();
+    let inverse = builder.node(builder.parents(product)[1]).unwrap();
+    assert_eq!(builder.derivation(inverse), Derivation::Hint);
+    inverse
+}
+
+#[tokio::test]
+async fn test_is_zero_forged_inverse() {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let out = builder.is_zero(x);
+    let inverse = inverse_hint(&builder, out);
+
+    // a prover claiming that a nonzero x is zero gives inv = 0, 
+    // which sets out to 1 and fails x * out == 0
+    builder.set(x, F::from(5));
+    builder.fill_nodes_incremental().unwrap();
+    builder.override_value(inverse, F::zero()).unwrap();
+    builder.fill_nodes_incremental().unwrap();
+    assert_eq!(builder.get(out), F::one());
+    assert!(!builder.check_constraints().await);
+
+    // any other wrong inverse leaves out outside of {0, 1}, and fails as well
+    builder.override_value(inverse, F::from(3)).unwrap();
+    builder.fill_nodes_incremental().unwrap();
+    assert!(!builder.check_constraints().await);
+
+    // when x is zero, out is 1 whatever the inverse is
+    builder.set(x, F::zero());
+    builder.fill_nodes_incremental().unwrap();
+    let mut rng = StdRng::seed_from_u64(23);
+    for _ in 0..10 {
+        builder.override_value(inverse, F::from(rng.gen::<u64>())).unwrap();
+        builder.fill_nodes_incremental().unwrap();
+        assert_eq!(builder.get(out), F::one());
+        assert!(builder.check_constraints().await);
+    }
+}
+
+#[tokio::test]
+async fn test_override_value_restricted_to_witnesses() {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let seven = builder.constant(F::from(7));
+    let sum = builder.add(x, seven);
+    let out = builder.is_zero(sum);
+    let inverse = inverse_hint(&builder, out);
+    builder.set(x, F::from(5));
+    builder.fill_nodes().unwrap();
+
+    // constants, inputs and arithmetic outputs are rejected, so no refill can leave them forged
+    assert_eq!(builder.override_value(seven, F::one()), Err(BuilderError::NotOverridable {
+        id: seven.id,
+        derivation: Derivation::Const,
+    }));
+    assert!(matches!(builder.override_value(x, F::one()), Err(BuilderError::NotOverridable { .. })));
+    assert!(matches!(builder.override_value(sum, F::one()), Err(BuilderError::NotOverridable { .. })));
+    builder.fill_nodes_incremental().unwrap();
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(seven), F::from(7));
+    assert_eq!(builder.get(sum), F::from(12));
+    assert!(builder.check_constraints().await);
+
+    // an overridden hint output is restored by a full fill
+    builder.override_value(inverse, F::zero()).unwrap();
+    builder.fill_nodes_incremental().unwrap();
+    assert!(!builder.check_constraints().await);
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(inverse), F::from(12).inverse().unwrap());
+    assert!(builder.check_constraints().await);
+}
+
+#[tokio::test]
+async fn test_less_than_max_min() {
+    let num_bits = 16;
+    let mut builder = Builder::<F>::new();
+    let a = builder.init();
+    let b = builder.init();
+    builder.assert_range(a, num_bits);
+    builder.assert_range(b, num_bits);
+    let less = builder.less_than(a, b, num_bits);
+    let max = builder.max(a, b, num_bits);
+    let min = builder.min(a, b, num_bits);
+
+    let mut rng = StdRng::seed_from_u64(24);
+    let mut cases: Vec<(u64, u64)> = vec![(0, 0), (0, 65535), (65535, 0), (65535, 65535), (7, 8), (8, 7)];
+    cases.extend((0..50).map(|_| (rng.gen_range(0..1 << num_bits), rng.gen_range(0..1 << num_bits))));
+    for (a_val, b_val) in cases {
+        builder.batch_set(&[a, b], &[F::from(a_val), F::from(b_val)]);
+        builder.fill_nodes().unwrap();
+        assert_eq!(builder.get(less), from_bool(a_val < b_val));
+        assert_eq!(builder.get(max), F::from(a_val.max(b_val)));
+        assert_eq!(builder.get(min), F::from(a_val.min(b_val)));
+        assert!(builder.check_constraints().await);
+    }
+}
+
+#[tokio::test]
+async fn test_less_than_forged_bits() {
+    let num_bits = 8;
+    let mut builder = Builder::<F>::new();
+    let a = builder.init();
+    let b = builder.init();
+    let less = builder.less_than(a, b, num_bits);
+
+    // less = 1 - top bit, where the bits are the outputs of a single hint
+    let top_bit = builder.node(builder.parents(less)[0]).unwrap();
+    assert_eq!(builder.derivation(top_bit), Derivation::Hint);
+    let bits: Vec<Node> = (top_bit.id - num_bits..=top_bit.id).map(|id| builder.node(id).unwrap()).collect();
+
+    // 3 < 200, with d = 3 - 200 + 256 = 59
+    builder.batch_set(&[a, b], &[F::from(3), F::from(200)]);
+    builder.fill_nodes_incremental().unwrap();
+    assert_eq!(builder.get(less), F::one());
+    assert!(builder.check_constraints().await);
+
+    // flipping the top bit alone breaks the recomposition
+    builder.override_value(top_bit, F::one()).unwrap();
+    builder.fill_nodes_incremental().unwrap();
+    assert_eq!(builder.get(less), F::zero());
+    assert!(!builder.check_constraints().await);
+
+    // so does rewriting every bit as the decomposition of d + 256, 
+    // which does not fit in the bits below the top one
+    let forged = 59 + 256;
+    for (i, &bit) in bits.iter().enumerate() {
+        builder.override_value(bit, F::from((forged >> i) & 1)).unwrap();
+    }
+    builder.fill_nodes_incremental().unwrap();
+    assert!(!builder.check_constraints().await);
+
+    // and a non-boolean top bit, compensated in the recomposition, fails booleanity
+    builder.batch_set(&[a, b], &[F::from(3), F::from(200)]);
+    builder.fill_nodes().unwrap();
+    builder.override_value(top_bit, F::from(2)).unwrap();
+    builder.override_value(bits[num_bits - 1], F::from(59 >> 7) - F::from(4)).unwrap();
+    builder.fill_nodes_incremental().unwrap();
+    let report = builder.constraint_report().await;
+    assert_eq!(report.num_failed(), 2);
+}
+
+#[tokio::test]
+async fn test_inv_and_div() {
+    let mut builder = Builder::<F>::new();
+    let a = builder.init();
+    let b = builder.init();
+    let inverse = builder.inv(b);
+    let quotient = builder.div(a, b);
+
+    let mut rng = StdRng::seed_from_u64(25);
+    for _ in 0..50 {
+        let (a_val, b_val) = (F::from(rng.gen::<u64>()), F::from(rng.gen_range(1..u64::MAX)));
+        builder.batch_set(&[a, b], &[a_val, b_val]);
+        builder.fill_nodes().unwrap();
+        assert_eq!(builder.get(inverse) * b_val, F::one());
+        assert_eq!(builder.get(quotient) * b_val, a_val);
+        assert!(builder.check_constraints().await);
+    }
+
+    // division is exact in the field, unlike integer division
+    builder.batch_set(&[a, b], &[F::from(7), F::from(2)]);
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(quotient), F::from(7) * F::from(2).inverse().unwrap());
+    assert_ne!(builder.get(quotient), F::from(3));
+}
+
+#[test]
+fn test_inv_shares_one_constant() {
+    let mut builder = Builder::<F>::new();
+    let inputs = builder.batch_init(4);
+    for &input in &inputs {
+        builder.inv(input);
+    }
+
+    // the first call creates the one constant, and every call adds its own 2 nodes
+    let circuit = builder.finalize();
+    assert_eq!(circuit.num_nodes(), 4 + 1 + 4 * 2);
+}
+
+#[test]
+fn test_div_by_zero() {
+    let mut builder = Builder::<F>::new();
+    let a = builder.init();
+    let b = builder.init();
+    let quotient = builder.div(a, b);
+    let doubled = builder.add(quotient, quotient);
+
+    builder.batch_set(&[a, b], &[F::from(7), F::zero()]);
+    let error = builder.fill_nodes().unwrap_err();
+    assert_eq!(error, EvalError::Hint {
+        input_ids: vec![a.id, b.id],
+        output_ids: vec![quotient.id],
+        inputs: vec![F::from(7), F::zero()],
+        error: HintError::new("division by zero"),
+    });
+    assert!(builder.try_get(quotient).is_err());
+    assert!(builder.try_get(doubled).is_err());
+
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let inverse = builder.inv(x);
+    builder.set(x, F::zero());
+    match builder.fill_nodes() {
+        Err(EvalError::Hint { input_ids, output_ids, error, .. }) => {
+            assert_eq!(input_ids, vec![x.id]);
+            assert_eq!(output_ids, vec![inverse.id]);
+            assert_eq!(error.message(), "division by zero");
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_div_forged_quotient() {
+    let mut builder = Builder::<F>::new();
+    let a = builder.init();
+    let b = builder.init();
+    let quotient = builder.div(a, b);
+
+    // the truncated integer quotient 7 / 2 = 3 does not satisfy 2 * q == 7
+    builder.batch_set(&[a, b], &[F::from(7), F::from(2)]);
+    builder.fill_nodes_incremental().unwrap();
+    builder.override_value(quotient, F::from(3)).unwrap();
+    builder.fill_nodes_incremental().unwrap();
+    assert_eq!(builder.constraint_report().await.num_failed(), 1);
+
+    // 0 / 0 is the one case the assertion cannot pin down, so the hint rejects it
+    builder.batch_set(&[a, b], &[F::zero(), F::zero()]);
+    assert!(builder.fill_nodes_incremental().is_err());
+}
+
+// builds a Poseidon permutation over random inputs and compares it with the reference
+fn check_poseidon_permutation<G: Field>(params: &PoseidonParams<G>, rng: &mut StdRng) {
+    let mut builder = Builder::<G>::new();
+    let state = builder.batch_init(params.width());
+    let permuted = builder.poseidon_permutation(params, &state);
+    for _ in 0..3 {
+        let values: Vec<G> = (0..params.width()).map(|_| G::from(rng.gen::<u64>())).collect();
+        builder.batch_set(&state, &values);
+        builder.fill_nodes().unwrap();
+        let outputs: Vec<G> = permuted.iter().map(|&node| builder.get(node)).collect();
+        assert_eq!(outputs, params.permute(&values));
+    }
+}
+
+#[test]
+fn test_poseidon_alpha() {
+    assert_eq!(PoseidonParams::<F>::smallest_alpha(), 7);
+    assert_eq!(PoseidonParams::<Goldilocks>::smallest_alpha(), 7);
+    assert_eq!(PoseidonParams::<BabyBear>::smallest_alpha(), 7);
+    assert_eq!(PoseidonParams::<Mersenne31>::smallest_alpha(), 5);
+    assert_eq!(PoseidonParams::<Bn254Scalar>::smallest_alpha(), 5);
+}
+
+#[test]
+fn test_poseidon_reference() {
+    // one full round on each side, worked by hand from the state [0, 1]
+    let params = PoseidonParams::<F>::new(
+        2, 2, 0, 7,
+        vec![vec![F::from(2), F::from(1)], vec![F::from(1), F::from(3)]],
+        vec![vec![F::from(1), F::from(2)], vec![F::from(3), F::from(4)]],
+    );
+    // round 1: [1, 3] -> [1, 2187] -> [2 + 2187, 1 + 3 * 2187]
+    // round 2: [2192, 6566] -> [2192^7, 6566^7] -> mds
+    let (x, y) = (F::from(2192).pow(7), F::from(6566).pow(7));
+    let expected = vec![F::from(2) * x + y, x + F::from(3) * y];
+    assert_eq!(params.permute(&[F::zero(), F::one()]), expected);
+
+    // partial rounds only raise the first element
+    let params = PoseidonParams::<F>::new(
+        2, 0, 1, 7,
+        vec![vec![F::one(), F::zero()], vec![F::zero(), F::one()]],
+        vec![vec![F::from(1), F::from(1)]],
+    );
+    assert_eq!(params.permute(&[F::from(1), F::from(2)]), vec![F::from(128), F::from(3)]);
+}
+
+// The Grain LFSR with which the reference implementation of the Poseidon 
+// paper derives the parameters of its instances, seeded with a prime field, 
+// the x^alpha S-box, the bit size of the field, the width and the rounds
+struct Grain {
+    state: VecDeque<bool>,
+}
+
+impl Grain {
+    fn new(field_bits: u64, width: u64, full_rounds: u64, partial_rounds: u64) -> Self {
+        let fields = [(1, 2), (0, 4), (field_bits, 12), (width, 12), (full_rounds, 10), (partial_rounds, 10), ((1 << 30) - 1, 30)];
+        let state = fields.iter().flat_map(|&(value, bits)| (0..bits).rev().map(move |i| (value >> i) & 1 == 1)).collect();
+        let mut grain = Grain { state };
+        for _ in 0..160 {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> bool {
+        let bit = [62, 51, 38, 23, 13, 0].iter().fold(false, |acc, &i| acc ^ self.state[i]);
+        self.state.pop_front();
+        self.state.push_back(bit);
+        bit
+    }
+
+    // the output bits are filtered in pairs, keeping the second bit when the first is set
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let keep = self.step();
+            let bit = self.step();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    // the next num_bits bits as a big-endian integer, or None if it is not smaller than the modulus
+    fn next_canonical<G: Field>(&mut self, num_bits: usize) -> Option<G> {
+        let mut bytes = vec![0u8; G::NUM_BYTES];
+        for i in (0..num_bits).rev() {
+            if self.next_bit() {
+                bytes[i / 8] |= 1 << (i % 8);
+            }
+        }
+        G::from_canonical_bytes(&bytes)
+    }
+
+    // the next num_bits bits as a big-endian integer, reduced modulo the field
+    fn next_reduced<G: Field>(&mut self, num_bits: usize) -> G {
+        (0..num_bits).fold(G::zero(), |acc, _| acc + acc + if self.next_bit() { G::one() } else { G::zero() })
+    }
+}
+
+// A field element from its big-endian hexadecimal representation
+fn from_hex<G: Field>(hex: &str) -> G {
+    let mut bytes: Vec<u8> = (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect();
+    bytes.reverse();
+    bytes.resize(G::NUM_BYTES, 0);
+    G::from_canonical_bytes(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn test_poseidon_known_answer() {
+    // the x5_254_3 instance of the reference implementation, used by circomlib: 
+    // BN254, width 3, 8 full and 57 partial rounds, and x^5. Round constants 
+    // are rejection sampled below the modulus, and the Cauchy MDS matrix 
+    // 1 / (x_i + y_j) is built from the next 2 * width reduced samples. 
+    let (width, full_rounds, partial_rounds) = (3, 8, 57);
+    let mut grain = Grain::new(254, width as u64, full_rounds as u64, partial_rounds as u64);
+    let round_constants: Vec<Vec<Bn254Scalar>> = (0..full_rounds + partial_rounds).map(|_| (0..width).map(|_| {
+        iter::repeat_with(|| grain.next_canonical(254)).find_map(|constant| constant).unwrap()
+    }).collect()).collect();
+    let points: Vec<Bn254Scalar> = (0..2 * width).map(|_| grain.next_reduced(254)).collect();
+    let mds: Vec<Vec<Bn254Scalar>> = (0..width).map(|i| (0..width).map(|j| {
+        (points[i] + points[width + j]).inverse().unwrap()
+    }).collect()).collect();
+
+    assert_eq!(round_constants[0][0], from_hex("0ee9a592ba9a9518d05986d656f40c2114c4993c11bb29938d21d47304cd8e6e"));
+    assert_eq!(mds[0][0], from_hex("109b7f411ba0e4c9b2b70caf5c36a7b194be7c11ad24378bfedb68592ba8118b"));
+    let params = PoseidonParams::new(width, full_rounds, partial_rounds, 5, mds, round_constants);
+
+    // the published test vector of the permutation of [0, 1, 2]
+    let expected: Vec<Bn254Scalar> = [
+        "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a",
+        "0fca49b798923ab0239de1c9e7a4a9a2210312b6a2f616d18b5a87f9b628ae29",
+        "0e7ae82e40091e63cbd4f16a6d16310b3729d4b6e138fcf54110e2867045a30c",
+    ].iter().map(|hex| from_hex(hex)).collect();
+    let state = [Bn254Scalar::zero(), Bn254Scalar::one(), Bn254Scalar::from(2)];
+    assert_eq!(params.permute(&state), expected);
+
+    let mut builder = Builder::<Bn254Scalar>::new();
+    let inputs = builder.batch_init(width);
+    let permuted = builder.poseidon_permutation(&params, &inputs);
+    builder.batch_set(&inputs, &state);
+    builder.fill_nodes().unwrap();
+    assert_eq!(permuted.iter().map(|&node| builder.get(node)).collect::<Vec<_>>(), expected);
+    assert!(builder.check_constraints().await);
+}
+
+#[test]
+fn test_poseidon_permutation() {
+    let mut rng = StdRng::seed_from_u64(23);
+    check_poseidon_permutation(&PoseidonParams::<F>::generate(3, 8, 22), &mut rng);
+    check_poseidon_permutation(&PoseidonParams::<F>::generate(5, 8, 22), &mut rng);
+    check_poseidon_permutation(&PoseidonParams::<Goldilocks>::generate(8, 8, 22), &mut rng);
+    check_poseidon_permutation(&PoseidonParams::<BabyBear>::generate(4, 8, 13), &mut rng);
+    check_poseidon_permutation(&PoseidonParams::<Mersenne31>::generate(3, 8, 14), &mut rng);
+    check_poseidon_permutation(&PoseidonParams::<Bn254Scalar>::generate(3, 8, 57), &mut rng);
+}
+
+#[tokio::test]
+async fn test_poseidon_hash() {
+    let params = PoseidonParams::<F>::generate(3, 8, 22);
+    let mut rng = StdRng::seed_from_u64(24);
+    for len in 0..6 {
+        let mut builder = Builder::<F>::new();
+        let inputs = builder.batch_init(len);
+        let hash = builder.poseidon_hash(&params, &inputs);
+        let values: Vec<F> = (0..len).map(|_| F::from(rng.gen::<u64>())).collect();
+        builder.batch_set(&inputs, &values);
+        builder.fill_nodes().unwrap();
+        assert_eq!(builder.get(hash), params.hash(&values));
+        assert!(builder.check_constraints().await);
+    }
+
+    // the length in the capacity separates inputs padded with zeros
+    let a = F::from(5);
+    assert_ne!(params.hash(&[a]), params.hash(&[a, F::zero()]));
+    assert_ne!(params.hash(&[]), params.hash(&[F::zero()]));
+}
+
+#[test]
+fn test_poseidon_costs() {
+    let (width, full_rounds, partial_rounds) = (3, 8, 22);
+    let params = PoseidonParams::<F>::generate(width, full_rounds, partial_rounds);
+    let mut builder = Builder::<F>::new();
+    let state = builder.batch_init(width);
+    builder.poseidon_permutation(&params, &state);
+
+    // alpha = 7 takes 4 multiplications per S-box
+    let rounds = full_rounds + partial_rounds;
+    let linear = rounds * (width + width * width + width * (width - 1));
+    let sboxes = (full_rounds * width + partial_rounds) * 4;
+    let constants = width * width + rounds * width;
+    let circuit = builder.finalize();
+    assert_eq!(circuit.num_gates(), linear + sboxes);
+    assert_eq!(circuit.num_nodes(), width + constants + linear + sboxes);
+}
+
+#[test]
+#[should_panic(expected = "x^3 is not a permutation of the field")]
+fn test_poseidon_rejects_non_permutation() {
+    let params = PoseidonParams::<F>::generate(2, 2, 0);
+    PoseidonParams::new(2, 2, 0, 3, params.mds().to_vec(), params.round_constants().to_vec());
+}
+
+// the bits of a message, most significant bit of each byte first
+fn message_bits(bytes: &[u8]) -> Vec<F> {
+    bytes.iter().flat_map(|&byte| (0..8).rev().map(move |i| F::from(((byte >> i) & 1) as u64))).collect()
+}
+
+// the hex encoding of a digest read from its bit nodes
+fn digest_hex(builder: &Builder<F>, digest: &[Node]) -> String {
+    digest.chunks(8).map(|bits| {
+        let byte = bits.iter().fold(0u8, |acc, &bit| (acc << 1) | builder.get(bit).to_u64().unwrap() as u8);
+        format!("{:02x}", byte)
+    }).collect()
+}
+
+#[tokio::test]
+async fn test_sha256_nist_vectors() {
+    // FIPS 180-2 examples, and the empty message
+    let vectors: [(&[u8], &str); 3] = [
+        (b"", "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+        (b"abc", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+        (b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq", "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"),
+    ];
+    for (message, expected) in vectors {
+        let mut builder = Builder::<F>::new();
+        let bits = builder.batch_init(8 * message.len());
+        let digest = builder.sha256(&bits);
+        assert_eq!(digest.len(), 256);
+
+        builder.batch_set(&bits, &message_bits(message));
+        builder.fill_nodes().unwrap();
+        assert_eq!(digest_hex(&builder, &digest), expected);
+        assert!(builder.check_constraints().await);
+    }
+}
+
+#[tokio::test]
+async fn test_sha256_compress() {
+    // "abc" padded by hand into a single block, compressed from the initial hash value
+    let mut block = b"abc".to_vec();
+    block.push(0x80);
+    block.resize(63, 0);
+    block.push(24);
+
+    let mut builder = Builder::<F>::new();
+    let state = builder.sha256_initial_state();
+    let bits = builder.batch_init(512);
+    let digest = builder.sha256_compress(&state, &bits);
+    builder.batch_set(&bits, &message_bits(&block));
+    builder.fill_nodes().unwrap();
+    assert_eq!(digest_hex(&builder, &digest), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+
+    // the gate and assertion count of a compression
+    let report = builder.constraint_report().await;
+    assert!(report.is_satisfied());
+    assert_eq!(report.num_assertions(), 6560);
+    assert_eq!(builder.finalize().num_gates(), 50736);
+}
+
+#[tokio::test]
+async fn test_sha256_forged_digest() {
+    let mut builder = Builder::<F>::new();
+    let bits = builder.batch_init(24);
+    let digest = builder.sha256(&bits);
+    builder.batch_set(&bits, &message_bits(b"abc"));
+    builder.fill_nodes_incremental().unwrap();
+
+    // flipping a bit of the digest breaks the decomposition of the final addition
+    let flipped = F::one() - builder.get(digest[0]);
+    builder.override_value(digest[0], flipped).unwrap();
+    builder.fill_nodes_incremental().unwrap();
+    assert_eq!(builder.constraint_report().await.num_failed(), 1);
+}
+
+#[test]
+fn test_mimc() {
+    let params = MimcParams::<F>::generate();
+    assert_eq!(params.alpha(), 7);
+    assert_eq!(params.round_constants().len(), 23);
+    assert_eq!(MimcParams::<Bn254Scalar>::generate().round_constants().len(), 110);
+
+    let mut builder = Builder::<F>::new();
+    let key = builder.init();
+    let x = builder.init();
+    let ciphertext = builder.mimc_encrypt(&params, key, x);
+    let digest = builder.mimc_compress(&params, key, x);
+    let mut rng = StdRng::seed_from_u64(25);
+    for _ in 0..3 {
+        let (k, v) = (F::from(rng.gen::<u64>()), F::from(rng.gen::<u64>()));
+        builder.batch_set(&[key, x], &[k, v]);
+        builder.fill_nodes().unwrap();
+        assert_eq!(builder.get(ciphertext), params.encrypt(k, v));
+        assert_eq!(builder.get(digest), params.compress(k, v));
+    }
+
+    // each round adds the key and takes 4 multiplications for x^7
+    let mut builder = Builder::<F>::new();
+    let (key, x) = (builder.init(), builder.init());
+    builder.mimc_compress(&params, key, x);
+    assert_eq!(builder.finalize().num_gates(), 23 * 5 + 1);
+}
+
+// verifies every leaf of a tree in a circuit, with the witnesses of the native tree
+async fn check_merkle_tree(hash: &impl CircuitHash<F>, depth: usize) {
+    let mut rng = StdRng::seed_from_u64(depth as u64);
+    let leaves: Vec<F> = (0..1 << depth).map(|_| F::from(rng.gen::<u64>())).collect();
+    let tree = MerkleTree::new(hash, leaves);
+    assert_eq!(tree.depth(), depth);
+
+    let mut builder = Builder::<F>::new();
+    let leaf = builder.init();
+    let path = builder.batch_init(depth);
+    let index_bits = builder.batch_init(depth);
+    let root = builder.init();
+    builder.merkle_verify(hash, leaf, &path, &index_bits, root);
+
+    for index in 0..tree.leaves().len() {
+        let witness = tree.witness(index);
+        assert_eq!(witness.leaf, tree.leaves()[index]);
+        builder.batch_set(&[leaf, root], &[witness.leaf, tree.root()]);
+        builder.batch_set(&path, &witness.path);
+        builder.batch_set(&index_bits, &witness.index_bits);
+        builder.fill_nodes().unwrap();
+        assert!(builder.check_constraints().await, "leaf {} was rejected", index);
+    }
+}
+
+#[tokio::test]
+async fn test_merkle_verify() {
+    check_merkle_tree(&PoseidonParams::<F>::generate(3, 8, 22), 3).await;
+    check_merkle_tree(&MimcParams::<F>::generate(), 4).await;
+    check_merkle_tree(&MimcParams::<F>::generate(), 0).await;
+}
+
+#[tokio::test]
+async fn test_merkle_verify_rejects_wrong_witness() {
+    let hash = MimcParams::<F>::generate();
+    let tree = MerkleTree::new(&hash, (0..8).map(F::from).collect());
+    let mut builder = Builder::<F>::new();
+    let leaf = builder.init();
+    let path = builder.batch_init(3);
+    let index_bits = builder.batch_init(3);
+    let root = builder.init();
+    builder.merkle_verify(&hash, leaf, &path, &index_bits, root);
+
+    let witness = tree.witness(5);
+    let set_witness = |builder: &mut Builder<F>, leaf_value: F, siblings: &[F], bits: &[F]| {
+        builder.batch_set(&[leaf, root], &[leaf_value, tree.root()]);
+        builder.batch_set(&path, siblings);
+        builder.batch_set(&index_bits, bits);
+        builder.fill_nodes().unwrap();
+    };
+
+    // a leaf that is not in the tree
+    set_witness(&mut builder, F::from(42), &witness.path, &witness.index_bits);
+    assert_eq!(builder.constraint_report().await.num_failed(), 1);
+
+    // the right leaf at the wrong index, and with a wrong sibling
+    set_witness(&mut builder, witness.leaf, &witness.path, &tree.witness(4).index_bits);
+    assert_eq!(builder.constraint_report().await.num_failed(), 1);
+    let mut path_values = witness.path.clone();
+    path_values[2] = path_values[2] + F::one();
+    set_witness(&mut builder, witness.leaf, &path_values, &witness.index_bits);
+    assert_eq!(builder.constraint_report().await.num_failed(), 1);
+
+    // an index bit of 2 would make select compute 2 * a - b, and fails booleanity
+    let mut bits = witness.index_bits.clone();
+    bits[0] = F::from(2);
+    set_witness(&mut builder, witness.leaf, &witness.path, &bits);
+    assert!(!builder.check_constraints().await);
+
+    set_witness(&mut builder, witness.leaf, &witness.path, &witness.index_bits);
+    assert!(builder.check_constraints().await);
+}
+
+#[test]
+fn test_try_merkle_verify_errors() {
+    let hash = MimcParams::<F>::generate();
+    let mut builder = Builder::<F>::new();
+    let leaf = builder.init();
+    let path = builder.batch_init(3);
+    let index_bits = builder.batch_init(2);
+    let root = builder.init();
+    let mut other_builder = Builder::<F>::new();
+    let foreign = other_builder.batch_init(10)[9];
+
+    assert_eq!(builder.try_merkle_verify(&hash, leaf, &path, &index_bits, root),
+        Err(BuilderError::LengthMismatch { left: 3, right: 2 }));
+    assert_eq!(builder.try_merkle_verify(&hash, leaf, &path[..2], &index_bits, foreign),
+        Err(BuilderError::ForeignNode { id: foreign.id }));
+
+    // nothing was added to the circuit by the failed calls
+    let circuit = builder.finalize();
+    assert_eq!((circuit.num_nodes(), circuit.num_gates()), (7, 0));
+}
+
+#[test]
+#[should_panic(expected = "Length mismatch: 3 nodes on the left, 2 on the right")]
+fn test_merkle_verify_length_mismatch() {
+    let mut builder = Builder::<F>::new();
+    let leaf = builder.init();
+    let path = builder.batch_init(3);
+    let index_bits = builder.batch_init(2);
+    builder.merkle_verify(&MimcParams::<F>::generate(), leaf, &path, &index_bits, leaf);
+}
+
+// the number of nodes and gates of merkle_verify on a path of the given depth
+fn merkle_verify_size(hash: &impl CircuitHash<F>, depth: usize) -> (usize, usize) {
+    let mut builder = Builder::<F>::new();
+    let leaf = builder.init();
+    let path = builder.batch_init(depth);
+    let index_bits = builder.batch_init(depth);
+    let root = builder.init();
+    builder.merkle_verify(hash, leaf, &path, &index_bits, root);
+    let circuit = builder.finalize();
+    (circuit.num_nodes(), circuit.num_gates())
+}
+
+#[test]
+fn test_merkle_verify_shares_hash_constants() {
+    // per level: 1 booleanity multiplication, 2 selects of 3 gates and the hash
+    let poseidon = PoseidonParams::<F>::generate(3, 8, 22);
+    let mimc = MimcParams::<F>::generate();
+    for (level_gates, (shallow, deep)) in [
+        (1 + 6 + 2 + 724, (merkle_verify_size(&poseidon, 2), merkle_verify_size(&poseidon, 5))),
+        (1 + 6 + 116, (merkle_verify_size(&mimc, 2), merkle_verify_size(&mimc, 5))),
+    ] {
+        // each extra level adds its gates and its two input nodes, but no constant nodes
+        assert_eq!(deep.1 - shallow.1, 3 * level_gates);
+        assert_eq!(deep.0 - shallow.0, 3 * (level_gates + 2));
+    }
+}
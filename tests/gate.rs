@@ -0,0 +1,187 @@
+use takehome::builder::*;
+use takehome::error::*;
+use takehome::field::{Field, Fp};
+use takehome::gate::Gate;
+use std::sync::Arc;
+
+// the Goldilocks prime 2^64 - 2^32 + 1
+type F = Fp<0xffff_ffff_0000_0001>;
+
+// q = a / b, constrained by q * b == a
+struct DivGate;
+
+impl Gate<F> for DivGate {
+    fn name(&self) -> &str {
+        "div"
+    }
+
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn evaluate(&self, inputs: &[F]) -> Result<Vec<F>, HintError> {
+        let inverse = inputs[1].inverse().ok_or(HintError::new("division by zero"))?;
+        Ok(vec![inputs[0] * inverse])
+    }
+
+    fn constraints(&self, builder: &mut Builder<F>, inputs: &[Node], outputs: &[Node]) {
+        let product = builder.mul(outputs[0], inputs[1]);
+        builder.assert_equal(product, inputs[0]);
+    }
+}
+
+// (hi, lo) with x = hi * 2^32 + lo, constrained by the recomposition only
+struct SplitGate;
+
+impl Gate<F> for SplitGate {
+    fn name(&self) -> &str {
+        "split"
+    }
+
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        2
+    }
+
+    fn evaluate(&self, inputs: &[F]) -> Result<Vec<F>, HintError> {
+        let x = inputs[0].to_u64().ok_or(HintError::new("value does not fit in 64 bits"))?;
+        Ok(vec![F::from(x >> 32), F::from(x & 0xffff_ffff)])
+    }
+
+    fn constraints(&self, builder: &mut Builder<F>, inputs: &[Node], outputs: &[Node]) {
+        let recomposed = builder.linear_combination(&[(F::from(1 << 32), outputs[0]), (F::one(), outputs[1])], F::zero());
+        builder.assert_equal(recomposed, inputs[0]);
+    }
+}
+
+// a division gate whose evaluation is off by one, so its constraints fail
+struct ForgedDivGate;
+
+impl Gate<F> for ForgedDivGate {
+    fn name(&self) -> &str {
+        "forged div"
+    }
+
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn evaluate(&self, inputs: &[F]) -> Result<Vec<F>, HintError> {
+        Ok(vec![DivGate.evaluate(inputs)?[0] + F::one()])
+    }
+
+    fn constraints(&self, builder: &mut Builder<F>, inputs: &[Node], outputs: &[Node]) {
+        DivGate.constraints(builder, inputs, outputs)
+    }
+}
+
+#[tokio::test]
+async fn test_custom_gate() {
+    let mut builder = Builder::<F>::new();
+    let a = builder.init();
+    let b = builder.init();
+    let c = builder.init();
+
+    let div: Arc<dyn Gate<F>> = Arc::new(DivGate);
+    let q = builder.custom_gate(div.clone(), &[a, b])[0];
+    let r = builder.custom_gate(div, &[q, c])[0];
+
+    assert_eq!(builder.derivation(q), Derivation::Custom);
+    assert_eq!(builder.parents(r), &[q.id, c.id]);
+    assert_eq!(builder.depth(r), 2);
+
+    builder.batch_set(&[a, b, c], &[F::from(84), F::from(2), F::from(7)]);
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(q), F::from(42));
+    assert_eq!(builder.get(r), F::from(6));
+
+    // each use of the gate declared its assertion
+    let report = builder.constraint_report().await;
+    assert_eq!(report.num_assertions(), 2);
+    assert!(report.is_satisfied());
+}
+
+#[tokio::test]
+async fn test_custom_gate_many_outputs() {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let parts = builder.custom_gate(Arc::new(SplitGate), &[x]);
+    assert_eq!(parts.len(), 2);
+
+    builder.set(x, F::from(0x1234_5678_9abc_def0));
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(parts[0]), F::from(0x1234_5678));
+    assert_eq!(builder.get(parts[1]), F::from(0x9abc_def0));
+    assert!(builder.check_constraints().await);
+}
+
+#[tokio::test]
+async fn test_custom_gate_constraints_catch_forgery() {
+    let mut builder = Builder::<F>::new();
+    let a = builder.init();
+    let b = builder.init();
+    let q = builder.custom_gate(Arc::new(ForgedDivGate), &[a, b])[0];
+
+    builder.batch_set(&[a, b], &[F::from(10), F::from(5)]);
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(q), F::from(3));
+
+    let report = builder.constraint_report().await;
+    assert_eq!(report.num_failed(), 1);
+}
+
+#[test]
+fn test_custom_gate_errors() {
+    let mut builder = Builder::<F>::new();
+    let a = builder.init();
+    let b = builder.init();
+
+    assert_eq!(builder.try_custom_gate(Arc::new(DivGate), &[a]), Err(BuilderError::ArityMismatch {
+        gate: "div".to_string(),
+        expected: 2,
+        given: 1,
+    }));
+    let foreign = Builder::<F>::new().init();
+    assert!(matches!(builder.try_custom_gate(Arc::new(DivGate), &[a, foreign]), Err(BuilderError::ForeignNode { .. })));
+
+    let q = builder.custom_gate(Arc::new(DivGate), &[a, b])[0];
+    let doubled = builder.add(q, q);
+    builder.batch_set(&[a, b], &[F::from(1), F::zero()]);
+    let error = builder.fill_nodes().unwrap_err();
+    assert_eq!(error, EvalError::Gate {
+        gate: "div".to_string(),
+        input_ids: vec![a.id, b.id],
+        output_ids: vec![q.id],
+        inputs: vec![F::from(1), F::zero()],
+        error: HintError::new("division by zero"),
+    });
+    assert!(builder.try_get(doubled).is_err());
+}
+
+#[test]
+fn test_custom_gate_strategies_and_batches() {
+    let mut builder = Builder::<F>::new();
+    let inputs = builder.batch_init(3);
+    let parts = builder.custom_gate(Arc::new(SplitGate), &[inputs[0]]);
+    let q = builder.custom_gate(Arc::new(DivGate), &[parts[1], inputs[1]])[0];
+    let sum = builder.add(q, inputs[2]);
+    let values = [F::from(0xdead_beef_0000_0010), F::from(4), F::from(1)];
+    builder.batch_set(&inputs, &values);
+
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(sum), F::from(5));
+    builder.set_eval_strategy(EvalStrategy::Dataflow);
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(sum), F::from(5));
+
+    // a zero divisor fails only its own witness
+    let circuit = builder.finalize();
+    let rows = vec![values.to_vec(), vec![F::from(8), F::zero(), F::one()], vec![F::from(9), F::from(3), F::zero()]];
+    let results = circuit.evaluate_batch(&rows);
+    assert_eq!(results[0].as_ref().unwrap().get(sum), F::from(5));
+    assert!(matches!(results[1], Err(EvalError::Gate { .. })));
+    assert_eq!(results[2].as_ref().unwrap().get(sum), F::from(3));
+}
@@ -1,5 +1,12 @@
 use takehome::builder::*;
-use std::time::Instant;
+use takehome::error::*;
+use takehome::report::*;
+use takehome::field::{Field, Fp};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{sync::Arc, time::Instant};
+
+// the Goldilocks prime 2^64 - 2^32 + 1
+type F = Fp<0xffff_ffff_0000_0001>;
 
 #[test]
 fn test_basic_function() {
@@ -7,10 +14,10 @@ fn test_basic_function() {
   
     // instantiates an empty circuit with no nodes
     env_logger::init();
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
 
-    // NOTE: nodes here are cloned since Rust consumes
-    // the provided arguments to these functions. 
+    // NOTE: nodes are lightweight Copy handles, so they can
+    // be passed to these functions and reused afterwards. 
 
     // create an input node to the circuit
     // value of the input node must be specified later
@@ -22,123 +29,286 @@ fn test_basic_function() {
     // that creates a new node in the circuit containing
     // the product of the two input nodes (which in this
     // case is x). 
-    let x_squared = builder.mul(x.clone(), x.clone());
+    let x_squared = builder.mul(x, x);
 
     // create a constant value in the circuit that can be
     // used in later computations 
-    let five = builder.constant(5);
+    let five = builder.constant(F::from(5));
 
     // create two addition gates 
-    let x_squared_plus_5 = builder.add(x_squared.clone(), five.clone());
-    let y = builder.add(x_squared_plus_5.clone(), x.clone());
+    let x_squared_plus_5 = builder.add(x_squared, five);
+    let y = builder.add(x_squared_plus_5, x);
 
     // set the value of the input nodes
     // Note that if not all input nodes are filled out
     // the builder will try to fill out nodes whose values
     // can be derived and fail when it first encounters a node
     // whose value depends on an unset input. 
-    builder.set(x.clone(), 5);
+    builder.set(x, F::from(5));
 
     // fill out the graph based on the input nodes 
-    builder.fill_nodes();
+    builder.fill_nodes().unwrap();
 
-    assert_eq!(x.get(), 5);
-    assert_eq!(x_squared.get(), 25);
-    assert_eq!(five.get(), 5);
-    assert_eq!(x_squared_plus_5.get(), 30);
-    assert_eq!(y.get(), 35);
+    assert_eq!(builder.get(x), F::from(5));
+    assert_eq!(builder.get(x_squared), F::from(25));
+    assert_eq!(builder.get(five), F::from(5));
+    assert_eq!(builder.get(x_squared_plus_5), F::from(30));
+    assert_eq!(builder.get(y), F::from(35));
 }
 
 #[test]
 fn test_multiple_access() {
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
 
     let x = builder.init();
     let y = builder.init();
     let z = builder.init();
     let w = builder.init(); 
 
-    let x2 = builder.mul(x.clone(), x.clone());
-    let xy = builder.mul(x.clone(), y.clone());
-    let xz = builder.mul(x.clone(), z.clone());
-    let xw = builder.mul(x.clone(), w.clone());
-
-    builder.set(x.clone(), 5);
-    builder.set(y.clone(), 5);
-    builder.set(z.clone(), 45);
-    builder.set(w.clone(), 6);
-
-    builder.fill_nodes();
-    assert_eq!(x.get(), 5);
-    assert_eq!(y.get(), 5);
-    assert_eq!(z.get(), 45);
-    assert_eq!(w.get(), 6);
-
-    assert_eq!(x2.get(), 25);
-    assert_eq!(xy.get(), 25);
-    assert_eq!(xz.get(), 225);
-    assert_eq!(xw.get(), 30);
+    let x2 = builder.mul(x, x);
+    let xy = builder.mul(x, y);
+    let xz = builder.mul(x, z);
+    let xw = builder.mul(x, w);
+
+    builder.set(x, F::from(5));
+    builder.set(y, F::from(5));
+    builder.set(z, F::from(45));
+    builder.set(w, F::from(6));
+
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(x), F::from(5));
+    assert_eq!(builder.get(y), F::from(5));
+    assert_eq!(builder.get(z), F::from(45));
+    assert_eq!(builder.get(w), F::from(6));
+
+    assert_eq!(builder.get(x2), F::from(25));
+    assert_eq!(builder.get(xy), F::from(25));
+    assert_eq!(builder.get(xz), F::from(225));
+    assert_eq!(builder.get(xw), F::from(30));
 }
 
 #[tokio::test]
 async fn test_constraints() {
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
     let a = builder.init();
-    let one = builder.constant(1); 
-    let eight = builder.constant(8);
+    let one = builder.constant(F::from(1)); 
+    let eight = builder.constant(F::from(8));
 
-    let b = builder.add(a.clone(), one.clone()); 
+    let b = builder.add(a, one); 
 
     let c = builder.init();
-    let c_times_8 = builder.mul(c.clone(), eight.clone());
+    let c_times_8 = builder.mul(c, eight);
 
-    builder.set(a.clone(), 13);
-    builder.set(c.clone(), 2);
+    builder.set(a, F::from(13));
+    builder.set(c, F::from(2));
 
-    builder.fill_nodes();
-    builder.assert_equal(c_times_8.clone(), b.clone());
+    builder.fill_nodes().unwrap();
+    builder.assert_equal(c_times_8, b);
 
     let constraints_check = builder.check_constraints().await;
 
     assert!(!constraints_check);
-    assert_eq!(a.get(), 13);
-    assert_eq!(one.get(), 1);
-    assert_eq!(eight.get(), 8);
-    assert_eq!(b.get(), 14);
-    assert_eq!(c.get(), 2);
-    assert_eq!(c_times_8.get(), 16);
+    assert_eq!(builder.get(a), F::from(13));
+    assert_eq!(builder.get(one), F::from(1));
+    assert_eq!(builder.get(eight), F::from(8));
+    assert_eq!(builder.get(b), F::from(14));
+    assert_eq!(builder.get(c), F::from(2));
+    assert_eq!(builder.get(c_times_8), F::from(16));
+}
+
+#[tokio::test]
+async fn test_constraint_report() {
+    let mut builder = Builder::<F>::new();
+    let a = builder.init();
+    let one = builder.constant(F::from(1)); 
+    let eight = builder.constant(F::from(8));
+
+    let b = builder.add(a, one); 
+
+    let c = builder.init();
+    let c_times_8 = builder.mul(c, eight);
+    let unset = builder.init();
+
+    // fails, passes, fails, and cannot be evaluated
+    builder.assert_equal(c_times_8, b);
+    builder.assert_equal(a, a);
+    builder.assert_equal(c, one);
+    builder.assert_equal(unset, a);
+
+    builder.set(a, F::from(13));
+    builder.set(c, F::from(2));
+
+    // the unset input only blocks the unevaluable assertion
+    builder.fill_nodes().unwrap();
+    let report = builder.constraint_report().await;
+
+    assert!(!report.is_satisfied());
+    assert_eq!(report.num_assertions(), 4);
+    assert_eq!(report.passed, 1);
+    assert_eq!(report.num_failed(), 2);
+    assert_eq!(report.num_unevaluable(), 1);
+
+    assert_eq!(report.failures[0], AssertionFailure {
+        index: 0,
+        left: NodeReport {
+            id: c_times_8.id,
+            value: Some(F::from(16)),
+            derivation: Derivation::Mul,
+            parents: vec![(c.id, Some(F::from(2))), (eight.id, Some(F::from(8)))],
+        },
+        right: NodeReport {
+            id: b.id,
+            value: Some(F::from(14)),
+            derivation: Derivation::Add,
+            parents: vec![(a.id, Some(F::from(13))), (one.id, Some(F::from(1)))],
+        },
+    });
+    assert_eq!(report.failures[1].index, 2);
+    assert_eq!(report.failures[1].left.derivation, Derivation::Input);
+    assert_eq!(report.failures[1].right.derivation, Derivation::Const);
+
+    assert_eq!(report.unevaluable, vec![UnevaluableAssertion {
+        index: 3,
+        left_id: unset.id,
+        right_id: a.id,
+        unfilled_ids: vec![unset.id],
+    }]);
+    assert!(report.to_string().starts_with("4 assertions: 1 passed, 2 failed, 1 unevaluable"));
+
+    // check_constraints no longer panics on the unfilled node
+    assert!(!builder.check_constraints().await);
+}
+
+#[tokio::test]
+async fn test_fill_and_check() {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let y = builder.init();
+
+    // assertions at several depths: x == y at depth 0, 
+    // x^2 == y^2 at depth 1 and x^4 == 16 at depth 2
+    let x2 = builder.mul(x, x);
+    let y2 = builder.mul(y, y);
+    let x4 = builder.mul(x2, x2);
+    let sixteen = builder.constant(F::from(16));
+    builder.assert_equal(x, y);
+    builder.assert_equal(x2, y2);
+    builder.assert_equal(x4, sixteen);
+
+    builder.set(x, F::from(2));
+    builder.set(y, -F::from(2));
+
+    let report = builder.fill_and_check(CheckMode::Complete).unwrap();
+    assert_eq!(report, builder.constraint_report().await);
+    assert_eq!(report.passed, 2);
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].index, 0);
+    assert_eq!(builder.get(x4), F::from(16));
+
+    // errors from gates are returned as they are by fill_nodes
+    let z = builder.init();
+    let failing = builder.hint(std::slice::from_ref(&z), |_: &[F]| Err(HintError::new("always fails")));
+    builder.set(z, F::zero());
+    match builder.fill_and_check(CheckMode::Complete) {
+        Err(EvalError::Hint { output_ids, .. }) => assert_eq!(output_ids, vec![failing.id]),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn test_fill_and_check_fail_fast() {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let one = builder.constant(F::one());
+
+    // an assertion that fails right after the first level, followed by 
+    // a deep chain of slow hints that should not be evaluated past the
+    // level that runs alongside the failing check
+    let x_plus_one = builder.add(x, one);
+    builder.assert_equal(x_plus_one, x);
+
+    let mut chain = Vec::new();
+    let mut node = x;
+    for _ in 0..200 {
+        node = builder.hint(std::slice::from_ref(&node), |val: &[F]| {
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            Ok(val[0] + F::one())
+        });
+        chain.push(node);
+    }
+    builder.assert_equal(node, x);
+
+    builder.set(x, F::from(5));
+    let report = builder.fill_and_check(CheckMode::FailFast).unwrap();
+
+    assert_eq!(report.num_failed(), 1);
+    assert_eq!(report.failures[0].index, 0);
+    assert_eq!(report.num_unevaluable(), 1);
+    assert_eq!(report.unevaluable[0].unfilled_ids, vec![node.id]);
+    assert!(builder.try_get(node).is_err());
+    assert_eq!(builder.get(chain[0]), F::from(6));
+    assert_eq!(builder.get(chain[1]), F::from(7));
+    assert!(builder.try_get(chain[2]).is_err());
+
+    // without fail-fast, the whole chain is evaluated and both assertions fail
+    let report = builder.fill_and_check(CheckMode::Complete).unwrap();
+    assert_eq!(report.num_failed(), 2);
+    assert_eq!(builder.get(node), F::from(205));
+}
+
+#[test]
+fn test_fill_and_check_fail_fast_ignores_unevaluable() {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let unset = builder.init();
+    let one = builder.constant(F::one());
+
+    // an assertion on an input that is never set, ready before any gate runs, 
+    // and an assertion that only fails two levels deeper
+    builder.assert_equal(unset, one);
+    let x2 = builder.mul(x, x);
+    let x4 = builder.mul(x2, x2);
+    builder.assert_equal(x4, one);
+
+    builder.set(x, F::from(2));
+    let report = builder.fill_and_check(CheckMode::FailFast).unwrap();
+    assert_eq!(report.num_unevaluable(), 1);
+    assert_eq!(report.unevaluable[0].unfilled_ids, vec![unset.id]);
+    assert_eq!(report.num_failed(), 1);
+    assert_eq!(report.failures[0].index, 1);
+    assert_eq!(builder.get(x4), F::from(16));
 }
 
 #[tokio::test]
 async fn test_hints() {
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
     let a = builder.init();
-    let one = builder.constant(1); 
-    let eight = builder.constant(8);
+    let one = builder.constant(F::from(1)); 
+    let eight = builder.constant(F::from(8));
 
-    let b = builder.add(a.clone(), one.clone()); 
+    let b = builder.add(a, one); 
 
-    fn lambda_div8(val: Vec<u32>) -> u32 {
+    fn lambda_div8(val: &[F]) -> Result<F, HintError> {
         assert_eq!(val.len(), 1);
-        val[0] / 8
+        Ok(F::from(val[0].to_u64().unwrap() / 8))
     }    
 
-    let c = builder.hint(&[b.clone()], lambda_div8);
-    let c_times_8 = builder.mul(c.clone(), eight.clone());
+    let c = builder.hint(std::slice::from_ref(&b), lambda_div8);
+    let c_times_8 = builder.mul(c, eight);
 
-    builder.set(a.clone(), 15);
-    builder.fill_nodes();
-    builder.assert_equal(c_times_8.clone(), b.clone());
+    builder.set(a, F::from(15));
+    builder.fill_nodes().unwrap();
+    builder.assert_equal(c_times_8, b);
 
     let constraints_check = builder.check_constraints().await;
 
     assert!(constraints_check);
-    assert_eq!(a.get(), 15);
-    assert_eq!(one.get(), 1);
-    assert_eq!(eight.get(), 8);
-    assert_eq!(b.get(), 16);
-    assert_eq!(c.get(), 2);
-    assert_eq!(c_times_8.get(), 16);
+    assert_eq!(builder.get(a), F::from(15));
+    assert_eq!(builder.get(one), F::from(1));
+    assert_eq!(builder.get(eight), F::from(8));
+    assert_eq!(builder.get(b), F::from(16));
+    assert_eq!(builder.get(c), F::from(2));
+    assert_eq!(builder.get(c_times_8), F::from(16));
 }
 
 #[tokio::test]
@@ -147,14 +317,14 @@ async fn test_sqrt_hints() {
     //
     // Assume that x+7 is a perfect square (so x = 2 or 9, etc.).
 
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
     let x = builder.init();
-    let seven = builder.constant(7);
-    let x_plus_seven = builder.add(x.clone(), seven.clone());
+    let seven = builder.constant(F::from(7));
+    let x_plus_seven = builder.add(x, seven);
 
     // Function to use for hint 
-    fn lambda_sqrt(val: Vec<u32>) -> u32 {
-        ((val[0] as f64).sqrt().round()) as u32
+    fn lambda_sqrt(val: &[F]) -> Result<F, HintError> {
+        Ok(F::from((val[0].to_u64().unwrap() as f64).sqrt().round() as u64))
     }
 
     // API for hints.
@@ -167,18 +337,18 @@ async fn test_sqrt_hints() {
     // For example, this computes the square root of x+7
     // by passing in the node x_plus_seven as an argument to
     // lambda_sqrt. 
-    let sqrt_x_plus_7 = builder.hint(&[x_plus_seven.clone()], lambda_sqrt);
-    let computed_sq = builder.mul(sqrt_x_plus_7.clone(), sqrt_x_plus_7.clone());
-
-    builder.assert_equal(computed_sq.clone(), x_plus_seven.clone());
-    builder.set(x.clone(), 2);
-    builder.fill_nodes();
-
-    assert_eq!(x.get(), 2);
-    assert_eq!(seven.get(), 7);
-    assert_eq!(sqrt_x_plus_7.get(), 3);
-    assert_eq!(computed_sq.get(), 9);
-    assert_eq!(x_plus_seven.get(), 9);
+    let sqrt_x_plus_7 = builder.hint(std::slice::from_ref(&x_plus_seven), lambda_sqrt);
+    let computed_sq = builder.mul(sqrt_x_plus_7, sqrt_x_plus_7);
+
+    builder.assert_equal(computed_sq, x_plus_seven);
+    builder.set(x, F::from(2));
+    builder.fill_nodes().unwrap();
+
+    assert_eq!(builder.get(x), F::from(2));
+    assert_eq!(builder.get(seven), F::from(7));
+    assert_eq!(builder.get(sqrt_x_plus_7), F::from(3));
+    assert_eq!(builder.get(computed_sq), F::from(9));
+    assert_eq!(builder.get(x_plus_seven), F::from(9));
     assert!(builder.check_constraints().await);
 }
 
@@ -186,36 +356,56 @@ async fn test_sqrt_hints() {
 async fn test_subtraction_hints() {
     // Example 4: f(x, y) = x - y
     //
-    // Assume that x >= y since otherwise an underflow error will occur
+    // Subtraction is modular, so x < y wraps around the field modulus
 
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
     let x = builder.init();
     let y = builder.init();
 
-    fn lambda_sub(val: Vec<u32>) -> u32 {
-        val[0] - val[1]
+    fn lambda_sub(val: &[F]) -> Result<F, HintError> {
+        Ok(val[0] - val[1])
     }
 
     // z will hold the value of x - y, and we will hint it
-    let z = builder.hint(&[x.clone(), y.clone()], lambda_sub); 
+    let z = builder.hint(&[x, y], lambda_sub); 
 
     // we can constrain the value of z by declaring z + y = x
-    let z_plus_y = builder.add(z.clone(), y.clone()); 
-    builder.assert_equal(z_plus_y.clone(), x.clone()); 
+    let z_plus_y = builder.add(z, y); 
+    builder.assert_equal(z_plus_y, x); 
     
-    builder.set(x.clone(), 23);
-    builder.set(y.clone(), 7); 
+    builder.set(x, F::from(23));
+    builder.set(y, F::from(7)); 
 
-    builder.fill_nodes();
+    builder.fill_nodes().unwrap();
     let constraints_check = builder.check_constraints().await; 
 
-    assert_eq!(x.get(), 23); 
-    assert_eq!(y.get(), 7); 
-    assert_eq!(z.get(), 16); 
-    assert_eq!(z_plus_y.get(), 23); 
+    assert_eq!(builder.get(x), F::from(23)); 
+    assert_eq!(builder.get(y), F::from(7)); 
+    assert_eq!(builder.get(z), F::from(16)); 
+    assert_eq!(builder.get(z_plus_y), F::from(23)); 
     assert!(constraints_check)
 }
 
+#[tokio::test]
+async fn test_subtraction_gate() {
+    // f(x, y) = x - y, computed by a subtraction gate rather than a hint, 
+    // so that no assertion is needed to constrain it
+
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let y = builder.init();
+    let z = builder.sub(x, y);
+    let z_plus_y = builder.add(z, y);
+    builder.assert_equal(z_plus_y, x);
+
+    builder.set(x, F::from(7));
+    builder.set(y, F::from(23));
+    builder.fill_nodes().unwrap();
+
+    assert_eq!(builder.get(z), -F::from(16));
+    assert!(builder.check_constraints().await);
+}
+
 #[tokio::test]
 async fn test_two_bit_decomposition() {
     // Example 4: f(z) = (b0, b1)
@@ -225,44 +415,44 @@ async fn test_two_bit_decomposition() {
     // 0<= b0, b1 <= 1
     // Assuming that 0 <= z <= 3
 
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
     let z = builder.init();
     
-    fn lambda_b0(val: Vec<u32>) -> u32 {
-        val[0] >> 1
+    fn lambda_b0(val: &[F]) -> Result<F, HintError> {
+        Ok(F::from(val[0].to_u64().unwrap() >> 1))
     }
 
-    fn lambda_b1(val: Vec<u32>) -> u32 {
-        val[0] % 2
+    fn lambda_b1(val: &[F]) -> Result<F, HintError> {
+        Ok(F::from(val[0].to_u64().unwrap() % 2))
     }
 
-    fn lambda_1_minus_x(val: Vec<u32>) -> u32 {
-        1 - val[0] 
+    fn lambda_1_minus_x(val: &[F]) -> Result<F, HintError> {
+        Ok(F::one() - val[0])
     }
 
-    let b0 = builder.hint(&[z.clone()], lambda_b0); 
-    let b1 = builder.hint(&[z.clone()], lambda_b1); 
-    let b0_bar = builder.hint(&[b0.clone()], lambda_1_minus_x);
-    let b1_bar = builder.hint(&[b1.clone()], lambda_1_minus_x);
+    let b0 = builder.hint(std::slice::from_ref(&z), lambda_b0); 
+    let b1 = builder.hint(std::slice::from_ref(&z), lambda_b1); 
+    let b0_bar = builder.hint(std::slice::from_ref(&b0), lambda_1_minus_x);
+    let b1_bar = builder.hint(std::slice::from_ref(&b1), lambda_1_minus_x);
 
-    let one = builder.constant(1); 
-    let zero = builder.constant(0); 
-    let two = builder.constant(2); 
+    let one = builder.constant(F::from(1)); 
+    let zero = builder.constant(F::from(0)); 
+    let two = builder.constant(F::from(2)); 
 
     // this assertion is to verify b0 + bo_bar = 1, so that we can
     // ensure that the circuit correctly constrains the value of b0_bar
-    let b0_sum = builder.add(b0_bar.clone(), b0.clone()); 
-    builder.assert_equal(b0_sum, one.clone());
+    let b0_sum = builder.add(b0_bar, b0); 
+    builder.assert_equal(b0_sum, one);
 
-    let b1_sum = builder.add(b1_bar.clone(), b1.clone()); 
+    let b1_sum = builder.add(b1_bar, b1); 
     builder.assert_equal(b1_sum, one);
 
     // this assertion is to verify b0 * bo_bar = 0, so that we can
     // know that b0 is either 0 or 1 for sure. 
-    let b0_prod = builder.mul(b0_bar, b0.clone()); 
-    builder.assert_equal(b0_prod, zero.clone());
+    let b0_prod = builder.mul(b0_bar, b0); 
+    builder.assert_equal(b0_prod, zero);
     
-    let b1_prod = builder.mul(b1_bar, b1.clone()); 
+    let b1_prod = builder.mul(b1_bar, b1); 
     builder.assert_equal(b1_prod, zero);
 
     let b0_times_two = builder.mul(b0, two); 
@@ -270,11 +460,11 @@ async fn test_two_bit_decomposition() {
 
     // this is to verify 2*b0 + b1 = z,
     // i.e. this verifies that the binary representation is correct. 
-    builder.assert_equal(sum, z.clone());
+    builder.assert_equal(sum, z);
 
     for i in 0..4 {
-        builder.set(z.clone(), i); 
-        builder.fill_nodes();
+        builder.set(z, F::from(i)); 
+        builder.fill_nodes().unwrap();
 
         let constraint_check = builder.check_constraints().await; 
         assert!(constraint_check);
@@ -282,34 +472,296 @@ async fn test_two_bit_decomposition() {
 
 }
 
+#[tokio::test]
+async fn test_multi_output_hints() {
+    // f(z) = (b0, b1) as in test_two_bit_decomposition, together with
+    // (q, r) = (z / 3, z % 3), each pair computed by a single hint. 
+    let mut builder = Builder::<F>::new();
+    let z = builder.init();
+
+    let bits = builder.hint_many(std::slice::from_ref(&z), 2, |val: &[F]| {
+        let z = val[0].to_u64().unwrap();
+        Ok(vec![F::from(z >> 1), F::from(z % 2)])
+    });
+    let (b0, b1) = (bits[0], bits[1]);
+
+    let three = builder.constant(F::from(3));
+    let division = builder.hint_many(&[z, three], 2, |val: &[F]| {
+        let (z, d) = (val[0].to_u64().unwrap(), val[1].to_u64().unwrap());
+        Ok(vec![F::from(z / d), F::from(z % d)])
+    });
+    let (q, r) = (division[0], division[1]);
+
+    // every output records the same parents
+    assert_eq!(builder.parents(b0), &[z.id]);
+    assert_eq!(builder.parents(b1), &[z.id]);
+    assert_eq!(builder.parents(q), &[z.id, three.id]);
+    assert_eq!(builder.parents(r), &[z.id, three.id]);
+    assert_eq!(builder.derivation(b0), Derivation::Hint);
+    assert_eq!(builder.derivation(r), Derivation::Hint);
+
+    let two = builder.constant(F::from(2));
+    let b0_times_two = builder.mul(b0, two);
+    let sum = builder.add(b0_times_two, b1);
+    builder.assert_equal(sum, z);
+
+    let q_times_three = builder.mul(q, three);
+    let recomposed = builder.add(q_times_three, r);
+    builder.assert_equal(recomposed, z);
+
+    for i in 0..4 {
+        builder.set(z, F::from(i)); 
+        builder.fill_nodes().unwrap();
+
+        assert_eq!(builder.get(b0), F::from(i >> 1));
+        assert_eq!(builder.get(b1), F::from(i & 1));
+        assert_eq!(builder.get(q), F::from(i / 3));
+        assert_eq!(builder.get(r), F::from(i % 3));
+        assert!(builder.check_constraints().await);
+    }
+}
+
 #[tokio::test]
 async fn test_lambda_gates() {  
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
 
     let a = builder.init();
     let b = builder.init();
 
-    let c = builder.mul(a.clone(), b.clone());
+    let c = builder.mul(a, b);
 
-    fn lambda_div(params: Vec<u32>) -> u32 {
-        params[0] / params[1]
+    fn lambda_div(params: &[F]) -> Result<F, HintError> {
+        let divisor = params[1].to_u64().unwrap();
+        if divisor == 0 {
+            return Err(HintError::new("division by zero"));
+        }
+        Ok(F::from(params[0].to_u64().unwrap() / divisor))
     }
 
-    let d = builder.hint(&[c.clone(), b.clone()], lambda_div);
+    let d = builder.hint(&[c, b], lambda_div);
 
-    builder.assert_equal(d.clone(), a.clone());
+    builder.assert_equal(d, a);
 
-    builder.set(a.clone(), 234); 
-    builder.set(b.clone(), 123);
+    builder.set(a, F::from(234)); 
+    builder.set(b, F::from(123));
 
-    builder.fill_nodes();
+    builder.fill_nodes().unwrap();
     let passed_constraints = builder.check_constraints().await; 
 
     assert!(passed_constraints);
-    assert_eq!(a.get(), 234);
-    assert_eq!(b.get(), 123);
-    assert_eq!(c.get(), 28782);
-    assert_eq!(d.get(), 234);
+    assert_eq!(builder.get(a), F::from(234));
+    assert_eq!(builder.get(b), F::from(123));
+    assert_eq!(builder.get(c), F::from(28782));
+    assert_eq!(builder.get(d), F::from(234));
+}
+
+#[test]
+fn test_failing_hints() {
+    let mut builder = Builder::<F>::new();
+
+    let a = builder.init();
+    let b = builder.init();
+
+    // the division fails when b is zero, while the sum at
+    // the same level is unaffected
+    let quotient = builder.hint(&[a, b], |val: &[F]| {
+        let divisor = val[1].to_u64().unwrap();
+        if divisor == 0 {
+            return Err(HintError::new("division by zero"));
+        }
+        Ok(F::from(val[0].to_u64().unwrap() / divisor))
+    });
+    let sum = builder.add(a, b);
+    let doubled = builder.add(quotient, quotient);
+
+    builder.set(a, F::from(12));
+    builder.set(b, F::from(4));
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(doubled), F::from(6));
+
+    builder.set(b, F::from(0));
+    let error = builder.fill_nodes().unwrap_err();
+
+    assert_eq!(error, EvalError::Hint {
+        input_ids: vec![a.id, b.id],
+        output_ids: vec![quotient.id],
+        inputs: vec![F::from(12), F::from(0)],
+        error: HintError::new("division by zero"),
+    });
+    assert_eq!(error.to_string(), format!(
+        "Hint gate with inputs [{}, {}] and outputs [{}] failed on values [12, 0]: division by zero", 
+        a.id, b.id, quotient.id));
+
+    // gates at the same level are still evaluated, while the failed
+    // output and everything after it is left unfilled
+    assert_eq!(builder.get(sum), F::from(12));
+    assert!(builder.try_get(quotient).is_err());
+    assert!(builder.try_get(doubled).is_err());
+}
+
+#[test]
+fn test_hint_output_count() {
+    let mut builder = Builder::<F>::new();
+
+    let x = builder.init();
+    let outputs = builder.hint_many(std::slice::from_ref(&x), 3, |val: &[F]| Ok(vec![val[0]; 2]));
+
+    builder.set(x, F::from(5));
+    match builder.fill_nodes() {
+        Err(EvalError::HintOutputCount { output_ids, returned, .. }) => {
+            assert_eq!(output_ids, outputs.iter().map(|node| node.id).collect::<Vec<_>>());
+            assert_eq!(returned, 2);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+// Builds the same random circuit of additions, multiplications and hints
+// into a builder, returning the input nodes and every node of the circuit
+fn random_circuit(builder: &mut Builder<F>, num_inputs: usize, num_gates: usize) -> (Vec<Node>, Vec<Node>) {
+    let mut rng = StdRng::seed_from_u64(9);
+    let inputs = builder.batch_init(num_inputs);
+    let mut nodes = inputs.clone();
+    for _ in 0..num_gates {
+        let a = nodes[rng.gen_range(0..nodes.len())];
+        let b = nodes[rng.gen_range(0..nodes.len())];
+        let c = nodes[rng.gen_range(0..nodes.len())];
+        let node = match rng.gen_range(0..8) {
+            0 => builder.add(a, b),
+            1 => builder.mul(a, b),
+            2 => builder.sub(a, b),
+            3 => builder.neg(a),
+            4 => builder.add_const(a, F::from(rng.gen::<u64>())),
+            5 => builder.mul_const(a, F::from(rng.gen::<u64>())),
+            6 => builder.linear_combination(&[(F::from(rng.gen::<u64>()), a), (F::from(2), b), (F::from(rng.gen::<u64>()), c)], F::one()),
+            _ => builder.hint(&[a, b], |val: &[F]| Ok(val[0] * val[0] * val[0] - val[1])),
+        };
+        nodes.push(node);
+    }
+    (inputs, nodes)
+}
+
+fn values(builder: &Builder<F>, nodes: &[Node]) -> Vec<F> {
+    nodes.iter().map(|&node| builder.get(node)).collect()
+}
+
+#[test]
+fn test_dataflow_strategy() {
+    let mut builder = Builder::<F>::new();
+    assert_eq!(builder.eval_strategy(), EvalStrategy::Level);
+    let (inputs, nodes) = random_circuit(&mut builder, 40, 3000);
+
+    let first: Vec<F> = (0..40).map(|i| F::from(i + 1)).collect();
+    let second: Vec<F> = (0..40).map(|i| F::from(1000 - i)).collect();
+
+    builder.batch_set(&inputs, &first);
+    builder.fill_nodes().unwrap();
+    let first_by_level = values(&builder, &nodes);
+
+    // alternate inputs between strategies, so that a node the dataflow
+    // scheduler skips would keep a value from the other set of inputs
+    builder.set_eval_strategy(EvalStrategy::Dataflow);
+    builder.batch_set(&inputs, &second);
+    builder.fill_nodes().unwrap();
+    let second_by_dataflow = values(&builder, &nodes);
+
+    builder.batch_set(&inputs, &first);
+    builder.fill_nodes().unwrap();
+    assert_eq!(values(&builder, &nodes), first_by_level);
+
+    builder.set_eval_strategy(EvalStrategy::Level);
+    builder.batch_set(&inputs, &second);
+    builder.fill_nodes().unwrap();
+    assert_eq!(values(&builder, &nodes), second_by_dataflow);
+}
+
+#[test]
+fn test_dataflow_failing_hints() {
+    let mut builder = Builder::<F>::new();
+    builder.set_eval_strategy(EvalStrategy::Dataflow);
+
+    let a = builder.init();
+    let b = builder.init();
+    let inverse = |val: &[F]| val[0].inverse().ok_or(HintError::new("zero has no inverse"));
+
+    // a failure in a long independent chain is deeper than the failing
+    // inverse of b, so the inverse of b is the one reported
+    let mut chain = a;
+    for _ in 0..20 {
+        chain = builder.add(chain, a);
+    }
+    let chain_inverse = builder.hint(std::slice::from_ref(&chain), inverse);
+    let b_inverse = builder.hint(std::slice::from_ref(&b), inverse);
+    let product = builder.mul(b_inverse, b);
+    let sum = builder.add(a, b);
+    let shallow = builder.add(chain, sum);
+
+    builder.set(a, F::from(0));
+    builder.set(b, F::from(3));
+    match builder.fill_nodes() {
+        Err(EvalError::Hint { output_ids, .. }) => assert_eq!(output_ids, vec![chain_inverse.id]),
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    builder.set(a, F::from(1));
+    builder.set(b, F::from(0));
+    let error = builder.fill_nodes().unwrap_err();
+    assert_eq!(error, EvalError::Hint {
+        input_ids: vec![b.id],
+        output_ids: vec![b_inverse.id],
+        inputs: vec![F::from(0)],
+        error: HintError::new("zero has no inverse"),
+    });
+
+    // as with the level strategy, gates at the failing level keep their 
+    // values while every later level is cleared, including nodes that 
+    // do not depend on the failing gate
+    assert_eq!(builder.get(sum), F::from(1));
+    assert!(builder.try_get(b_inverse).is_err());
+    assert!(builder.try_get(product).is_err());
+    assert!(builder.try_get(chain).is_err());
+    assert!(builder.try_get(chain_inverse).is_err());
+    assert!(builder.try_get(shallow).is_err());
+}
+
+#[tokio::test]
+async fn test_closure_hints() {
+    // f(x) = table[x] + key, where the table and the key are
+    // captured by the hints rather than passed in as nodes
+    let mut builder = Builder::<F>::new();
+
+    let table: Arc<Vec<u64>> = Arc::new((0..256).map(|i| (167 * i + 13) % 256).collect());
+    let key = F::from(1_000_003);
+
+    let inputs = builder.batch_init(256);
+    let outputs: Vec<_> = inputs.iter().map(|x| {
+        let table = table.clone();
+        builder.hint(std::slice::from_ref(x), move |val: &[F]| {
+            Ok(F::from(table[val[0].to_u64().unwrap() as usize]) + key)
+        })
+    }).collect();
+
+    // the table is a permutation of 0..256, so its inverse is used 
+    // to constrain every output back to its input
+    let mut inverse = vec![0; 256];
+    table.iter().enumerate().for_each(|(i, &v)| inverse[v as usize] = i as u64);
+    let checks: Vec<_> = outputs.iter().map(|y| {
+        let inverse = inverse.clone();
+        builder.hint(std::slice::from_ref(y), move |val: &[F]| {
+            let masked = (val[0] - key).to_u64().unwrap();
+            Ok(F::from(inverse[masked as usize]))
+        })
+    }).collect();
+    builder.batch_assert_equal(&checks, &inputs);
+
+    let values: Vec<F> = (0..256).map(|i| F::from(255 - i)).collect();
+    builder.batch_set(&inputs, &values);
+    builder.fill_nodes().unwrap();
+
+    for (i, &y) in outputs.iter().enumerate() {
+        assert_eq!(builder.get(y), F::from(table[255 - i]) + key);
+    }
+    assert!(builder.check_constraints().await);
 }
 
 #[tokio::test]
@@ -320,43 +772,43 @@ async fn test_large_input_builder() {
 
     // 1 million inputs 
     let start_time = Instant::now();
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
 
     let time_to_batch_init = Instant::now();
     let inputs = builder.batch_init(num_inputs);
     println!("Time to batch init: {:?}", Instant::now() - time_to_batch_init);
 
     let time_to_batch_const = Instant::now();
-    let constants = builder.batch_constant(&vec![2; num_inputs]);
+    let constants = builder.batch_constant(&vec![F::from(2); num_inputs]);
     println!("Time to batch const: {:?}", Instant::now() - time_to_batch_const);
 
     // to catch the intermediate additions and multplications. 
     let mut intermediates = Vec::with_capacity(num_inputs/2);
 
     for i in 0..num_inputs/4 {
-        intermediates.push(builder.add(inputs[2*i].clone(), inputs[2*i + 1].clone())); 
+        intermediates.push(builder.add(inputs[2*i], inputs[2*i + 1])); 
     }
 
     for i in 0..num_inputs/4 {
-        intermediates.push(builder.mul(inputs[0].clone(), inputs[i].clone())); 
+        intermediates.push(builder.mul(inputs[0], inputs[i])); 
     }
 
     for i in 0..num_inputs/8 {
-        builder.add(intermediates[2*i].clone(), intermediates[2*i + 1].clone()); 
+        builder.add(intermediates[2*i], intermediates[2*i + 1]); 
     }
     for i in num_inputs/8..num_inputs/4 {
-        builder.mul(intermediates[2*i].clone(), intermediates[2*i + 1].clone()); 
+        builder.mul(intermediates[2*i], intermediates[2*i + 1]); 
     }
 
     for i in 0..num_inputs {
-        builder.mul(constants[i].clone(), inputs[i].clone());
+        builder.mul(constants[i], inputs[i]);
     }
    
     builder.batch_assert_equal(&inputs, &inputs);
-    builder.batch_set(&inputs, &vec![100; num_inputs]);
+    builder.batch_set(&inputs, &vec![F::from(100); num_inputs]);
 
     let time_to_fill_nodes = Instant::now();
-    builder.fill_nodes();
+    builder.fill_nodes().unwrap();
     println!("Time to fill nodes: {:?}", Instant::now() - time_to_fill_nodes);
 
     let check_constraints = builder.check_constraints().await;
@@ -364,4 +816,181 @@ async fn test_large_input_builder() {
 
     println!("Elapsed time: {:?}", end_time - start_time);
     println!("Constraints Passed? {:?}", check_constraints);
-}
\ No newline at end of file
+}
+#[test]
+fn test_fill_nodes_incremental() {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let y = builder.init();
+    let z = builder.init();
+    let x_squared = builder.mul(x, x);
+    let y_squared = builder.mul(y, y);
+    let sum = builder.add(x_squared, y_squared);
+    let z_cubed = builder.hint(&[z], |val: &[F]| Ok(val[0] * val[0] * val[0]));
+    let total = builder.add(sum, z_cubed);
+
+    builder.batch_set(&[x, y, z], &[F::from(1), F::from(2), F::from(3)]);
+    // the first fill evaluates every gate
+    assert_eq!(builder.fill_nodes_incremental().unwrap(), 5);
+    assert_eq!(builder.get(total), F::from(32));
+
+    // only x_squared, sum and total depend on x
+    builder.set(x, F::from(4));
+    assert_eq!(builder.fill_nodes_incremental().unwrap(), 3);
+    assert_eq!(builder.get(total), F::from(47));
+    assert_eq!(builder.get(z_cubed), F::from(27));
+
+    // nothing changed since the last fill
+    assert_eq!(builder.fill_nodes_incremental().unwrap(), 0);
+
+    // adding a gate requires a full fill
+    let doubled = builder.add(total, total);
+    builder.set(z, F::from(1));
+    assert_eq!(builder.fill_nodes_incremental().unwrap(), 6);
+    assert_eq!(builder.get(doubled), F::from(42));
+}
+
+#[test]
+fn test_fill_nodes_incremental_random_circuit() {
+    let mut builder = Builder::<F>::new();
+    let (inputs, nodes) = random_circuit(&mut builder, 16, 500);
+    let mut rng = StdRng::seed_from_u64(13);
+    let values_in = (0..inputs.len()).map(|_| F::from(rng.gen_range(1..1000))).collect::<Vec<_>>();
+    builder.batch_set(&inputs, &values_in);
+    builder.fill_nodes_incremental().unwrap();
+
+    for _ in 0..10 {
+        let changed = inputs[rng.gen_range(0..inputs.len())];
+        builder.set(changed, F::from(rng.gen_range(1..1000)));
+        let evaluated = builder.fill_nodes_incremental().unwrap();
+        let incremental = values(&builder, &nodes);
+
+        // every gate reading the changed input is re-evaluated, and no more 
+        // gates than in the forward cone of the input
+        let cone = nodes.iter().filter(|&&node| depends_on(&builder, &nodes, node, changed)).count();
+        assert_eq!(evaluated, cone);
+
+        builder.fill_nodes().unwrap();
+        assert_eq!(incremental, values(&builder, &nodes));
+    }
+}
+
+// Whether a node is derived, directly or not, from another node, 
+// given every node of the builder in order of id
+fn depends_on(builder: &Builder<F>, nodes: &[Node], node: Node, ancestor: Node) -> bool {
+    builder.parents(node).iter().any(|&parent| parent == ancestor.id || depends_on(builder, nodes, nodes[parent], ancestor))
+}
+
+#[test]
+fn test_fill_nodes_incremental_hint_many() {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let y = builder.init();
+    let parts = builder.hint_many(&[x, y], 3, |val: &[F]| Ok(vec![val[0] + val[1], val[0] * val[1], val[0]]));
+    let sum = builder.add(parts[0], parts[2]);
+    let product = builder.mul(parts[1], y);
+
+    builder.batch_set(&[x, y], &[F::from(2), F::from(3)]);
+    assert_eq!(builder.fill_nodes_incremental().unwrap(), 3);
+
+    // the hint is evaluated once, although each of its outputs depends on y
+    builder.set(y, F::from(5));
+    assert_eq!(builder.fill_nodes_incremental().unwrap(), 3);
+    assert_eq!(builder.get(sum), F::from(9));
+    assert_eq!(builder.get(product), F::from(50));
+}
+
+#[test]
+fn test_fill_nodes_incremental_failing_hint() {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let y = builder.init();
+    let x_inverse = builder.hint(&[x], |val: &[F]| val[0].inverse().ok_or(HintError::new("zero has no inverse")));
+    let product = builder.mul(x_inverse, y);
+    let y_doubled = builder.add(y, y);
+
+    builder.batch_set(&[x, y], &[F::from(2), F::from(3)]);
+    assert_eq!(builder.fill_nodes_incremental().unwrap(), 3);
+
+    builder.set(x, F::from(0));
+    match builder.fill_nodes_incremental() {
+        Err(EvalError::Hint { output_ids, .. }) => assert_eq!(output_ids, vec![x_inverse.id]),
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert!(builder.try_get(product).is_err());
+    assert_eq!(builder.get(y_doubled), F::from(6));
+
+    // after a failure, the next fill evaluates every gate again
+    builder.set(x, F::from(1));
+    assert_eq!(builder.fill_nodes_incremental().unwrap(), 3);
+    assert_eq!(builder.get(product), F::from(3));
+}
+
+#[test]
+fn test_fill_nodes_partial() {
+    let mut builder = Builder::<F>::new();
+    let x = builder.init();
+    let y = builder.init();
+    let z = builder.init();
+    let two = builder.constant(F::from(2));
+    let x_doubled = builder.mul(x, two);
+    let y_squared = builder.mul(y, y);
+    let xy = builder.add(x_doubled, y_squared);
+    let z_inverse = builder.hint(&[z], |val: &[F]| val[0].inverse().ok_or(HintError::new("zero has no inverse")));
+    let total = builder.add(xy, z_inverse);
+
+    builder.set(x, F::from(3));
+    let partial = builder.fill_nodes_partial().unwrap();
+    assert!(!partial.is_complete());
+    assert_eq!(partial.missing_input_ids, vec![y.id, z.id]);
+    assert_eq!(partial.blocked, vec![
+        BlockedNode { id: y_squared.id, missing_input_ids: vec![y.id] },
+        BlockedNode { id: xy.id, missing_input_ids: vec![y.id] },
+        BlockedNode { id: z_inverse.id, missing_input_ids: vec![z.id] },
+        BlockedNode { id: total.id, missing_input_ids: vec![y.id, z.id] },
+    ]);
+    assert_eq!(builder.get(x_doubled), F::from(6));
+    assert!(builder.try_get(xy).is_err());
+
+    builder.set(y, F::from(4));
+    let partial = builder.fill_nodes_partial().unwrap();
+    assert_eq!(partial.missing_input_ids, vec![z.id]);
+    assert_eq!(partial.blocked.len(), 2);
+    assert_eq!(builder.get(xy), F::from(22));
+
+    // hint failures are still reported as errors
+    builder.set(z, F::from(0));
+    match builder.fill_nodes_partial() {
+        Err(EvalError::Hint { output_ids, .. }) => assert_eq!(output_ids, vec![z_inverse.id]),
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    builder.set(z, F::from(1));
+    assert!(builder.fill_nodes_partial().unwrap().is_complete());
+    assert_eq!(builder.get(total), F::from(23));
+}
+
+#[test]
+fn test_fill_nodes_partial_random_circuit() {
+    let mut builder = Builder::<F>::new();
+    let (inputs, nodes) = random_circuit(&mut builder, 16, 500);
+    let mut rng = StdRng::seed_from_u64(17);
+    let set = inputs.iter().filter(|_| rng.gen_bool(0.75)).copied().collect::<Vec<_>>();
+    for &input in &set {
+        builder.set(input, F::from(rng.gen_range(1..1000)));
+    }
+    let partial = builder.fill_nodes_partial().unwrap();
+
+    // a node is blocked exactly when it depends on an unset input
+    let unset = inputs.iter().filter(|input| !set.contains(input)).copied().collect::<Vec<_>>();
+    assert_eq!(partial.missing_input_ids, unset.iter().map(|node| node.id).collect::<Vec<_>>());
+    let blocked = nodes[inputs.len()..].iter()
+        .map(|&node| (node, unset.iter().filter(|&&input| depends_on(&builder, &nodes, node, input)).map(|node| node.id).collect::<Vec<_>>()))
+        .filter(|(_, missing)| !missing.is_empty())
+        .map(|(node, missing_input_ids)| BlockedNode { id: node.id, missing_input_ids })
+        .collect::<Vec<_>>();
+    assert_eq!(partial.blocked, blocked);
+    for &node in &nodes {
+        assert_eq!(builder.try_get(node).is_ok(), !blocked.iter().any(|blocked| blocked.id == node.id) && !unset.contains(&node));
+    }
+}
@@ -0,0 +1,183 @@
+use takehome::builder::*;
+use takehome::error::*;
+use takehome::field::{Field, Fp};
+use takehome::lookup::LookupTable;
+
+// the Goldilocks prime 2^64 - 2^32 + 1
+type F = Fp<0xffff_ffff_0000_0001>;
+
+#[test]
+fn test_lookup_tables() {
+    let xor = LookupTable::<F>::xor(8);
+    assert_eq!(xor.arity(), 2);
+    assert_eq!(xor.len(), 1 << 16);
+    assert_eq!(xor.get(&[F::from(0b1100), F::from(0b1010)]), Some(F::from(0b0110)));
+    assert_eq!(xor.get(&[F::from(256), F::zero()]), None);
+
+    let range = LookupTable::<F>::range(8);
+    assert_eq!(range.len(), 256);
+    assert!(range.contains(&[F::from(255)], F::from(255)));
+    assert!(!range.contains(&[F::from(255)], F::from(254)));
+    assert!(!range.contains(&[F::from(256)], F::from(256)));
+
+    let mut table = LookupTable::<F>::new(1);
+    assert!(table.is_empty());
+    table.insert(vec![F::from(3)], F::from(9));
+    assert_eq!(table.get(&[F::from(3)]), Some(F::from(9)));
+}
+
+#[test]
+#[should_panic(expected = "would have 2^64 rows")]
+fn test_lookup_table_too_wide() {
+    LookupTable::<F>::range(64);
+}
+
+#[test]
+#[should_panic(expected = "more than the 2^24 allowed")]
+fn test_lookup_table_too_many_rows() {
+    LookupTable::<F>::xor(13);
+}
+
+#[tokio::test]
+async fn test_lookup_xor() {
+    let mut builder = Builder::<F>::new();
+    let xor = builder.register_table(LookupTable::xor(8));
+    let bytes = builder.batch_init(4);
+
+    // the table is stored once and shared by every lookup on it
+    let left = builder.lookup(xor, &[bytes[0], bytes[1]]);
+    let right = builder.lookup(xor, &[bytes[2], bytes[3]]);
+    let out = builder.lookup(xor, &[left, right]);
+    assert_eq!(builder.derivation(out), Derivation::Lookup);
+    assert_eq!(builder.parents(out), &[left.id, right.id]);
+    assert_eq!(builder.depth(out), 2);
+    assert_eq!(builder.table(xor).len(), 1 << 16);
+
+    let values = [0x3c, 0xa5, 0xff, 0x12];
+    builder.batch_set(&bytes, &values.map(F::from));
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(left), F::from(0x3c ^ 0xa5));
+    assert_eq!(builder.get(out), F::from(0x3c ^ 0xa5 ^ 0xff ^ 0x12));
+
+    let report = builder.constraint_report().await;
+    assert_eq!(report.num_lookups(), 3);
+    assert_eq!(report.lookups_passed, 3);
+    assert!(report.is_satisfied());
+    assert!(builder.check_constraints().await);
+}
+
+#[tokio::test]
+async fn test_lookup_range_missing_row() {
+    let mut builder = Builder::<F>::new();
+    let range = builder.register_table(LookupTable::range(8));
+    let x = builder.init();
+    let checked = builder.lookup(range, &[x]);
+    let doubled = builder.add(checked, checked);
+
+    builder.set(x, F::from(200));
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(doubled), F::from(400));
+
+    // a value out of range has no row, and nothing downstream of it is filled
+    builder.set(x, F::from(256));
+    let error = builder.fill_nodes().unwrap_err();
+    assert_eq!(error, EvalError::Lookup {
+        table: 0,
+        input_ids: vec![x.id],
+        output_ids: vec![checked.id],
+        inputs: vec![F::from(256)],
+    });
+    assert!(builder.try_get(checked).is_err());
+    assert!(builder.try_get(doubled).is_err());
+
+    let report = builder.constraint_report().await;
+    assert_eq!(report.lookup_failures.len(), 1);
+    assert_eq!(report.lookup_failures[0].output, None);
+    assert!(!report.is_satisfied());
+}
+
+#[tokio::test]
+async fn test_lookup_forged_output() {
+    let mut builder = Builder::<F>::new();
+    let xor = builder.register_table(LookupTable::xor(4));
+    let a = builder.init();
+    let b = builder.init();
+    let out = builder.lookup(xor, &[a, b]);
+    let plus_one = builder.add_const(out, F::one());
+
+    builder.batch_set(&[a, b], &[F::from(5), F::from(3)]);
+    builder.fill_nodes().unwrap();
+    assert!(builder.check_constraints().await);
+
+    // a forged output is not a row of the table, even once its consumers are refilled
+    builder.override_value(out, F::from(7)).unwrap();
+    builder.fill_nodes_incremental().unwrap();
+    assert_eq!(builder.get(plus_one), F::from(8));
+
+    let report = builder.constraint_report().await;
+    assert_eq!(report.lookups_passed, 0);
+    assert_eq!(report.lookup_failures.len(), 1);
+    let failure = &report.lookup_failures[0];
+    assert_eq!((failure.index, failure.table, failure.output_id), (0, 0, out.id));
+    assert_eq!(failure.inputs, vec![Some(F::from(5)), Some(F::from(3))]);
+    assert_eq!(failure.output, Some(F::from(7)));
+    assert!(report.to_string().contains("1 lookups: 0 passed, 1 failed"));
+    assert!(!builder.check_constraints().await);
+}
+
+#[test]
+fn test_lookup_errors() {
+    let mut builder = Builder::<F>::new();
+    let xor = builder.register_table(LookupTable::xor(2));
+    let empty = builder.register_table(LookupTable::new(0));
+    let a = builder.init();
+
+    assert_eq!(builder.try_lookup(xor, &[a]), Err(BuilderError::ArityMismatch {
+        gate: "lookup into table 0".to_string(),
+        expected: 2,
+        given: 1,
+    }));
+    let error = builder.try_lookup(empty, &[]).unwrap_err();
+    assert_eq!(error, BuilderError::EmptyArguments);
+    assert_eq!(error.to_string(), "A hint, custom gate or lookup requires at least one input node");
+
+    let mut other = Builder::<F>::new();
+    let foreign_table = other.register_table(LookupTable::xor(2));
+    let foreign_node = other.init();
+    assert_eq!(builder.try_lookup(foreign_table, &[a, a]), Err(BuilderError::UnknownTable { index: 0 }));
+    assert_eq!(builder.try_table(foreign_table), Err(BuilderError::UnknownTable { index: 0 }));
+    assert_eq!(builder.try_table(xor).map(|table| table.arity()), Ok(2));
+
+    // a handle of another builder with an index past the tables of this one
+    other.register_table(LookupTable::range(2));
+    let past_end = other.register_table(LookupTable::range(2));
+    assert_eq!(builder.try_table(past_end), Err(BuilderError::UnknownTable { index: 2 }));
+    assert!(matches!(builder.try_lookup(xor, &[a, foreign_node]), Err(BuilderError::ForeignNode { .. })));
+}
+
+#[test]
+fn test_lookup_strategies_and_batches() {
+    let mut builder = Builder::<F>::new();
+    let xor = builder.register_table(LookupTable::xor(8));
+    let range = builder.register_table(LookupTable::range(8));
+    let inputs = builder.batch_init(3);
+    let mixed = builder.lookup(xor, &[inputs[0], inputs[1]]);
+    let sum = builder.add(mixed, inputs[2]);
+    let checked = builder.lookup(range, &[sum]);
+    let values = [F::from(0xf0), F::from(0x0f), F::zero()];
+    builder.batch_set(&inputs, &values);
+
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(checked), F::from(0xff));
+    builder.set_eval_strategy(EvalStrategy::Dataflow);
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(checked), F::from(0xff));
+
+    // a sum out of range fails only its own witness
+    let circuit = builder.finalize();
+    let rows = vec![values.to_vec(), vec![F::from(0xf0), F::from(0x0f), F::one()], vec![F::from(1), F::from(2), F::from(4)]];
+    let results = circuit.evaluate_batch(&rows);
+    assert_eq!(results[0].as_ref().unwrap().get(checked), F::from(0xff));
+    assert!(matches!(results[1], Err(EvalError::Lookup { table: 1, .. })));
+    assert_eq!(results[2].as_ref().unwrap().get(checked), F::from(7));
+}
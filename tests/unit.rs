@@ -1,10 +1,15 @@
 use takehome::builder::*;
+use takehome::error::*;
+use takehome::field::{Field, Fp};
 use rand::{rngs::StdRng, SeedableRng, Rng};
 
+// the Goldilocks prime 2^64 - 2^32 + 1
+type F = Fp<0xffff_ffff_0000_0001>;
+
 #[test]
 fn test_multiplication_gate() {
     env_logger::init();
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
 
     let seed = [0u8; 32];
     let mut rng = StdRng::from_seed(seed);
@@ -12,24 +17,24 @@ fn test_multiplication_gate() {
     let x = builder.init();
     let y = builder.init();
 
-    let z = builder.mul(x.clone(), y.clone());
+    let z = builder.mul(x, y);
 
     for _ in 0..10 {
         let x_val: u16 = rng.gen(); 
         let y_val: u16 = rng.gen();
 
-        builder.set(x.clone(), x_val.into()); 
-        builder.set(y.clone(), y_val.into()); 
+        builder.set(x, F::from(x_val as u64)); 
+        builder.set(y, F::from(y_val as u64)); 
 
-        builder.fill_nodes();
+        builder.fill_nodes().unwrap();
 
-        assert_eq!(z.get(), (x_val as u32 * y_val as u32)); 
+        assert_eq!(builder.get(z), F::from(x_val as u64 * y_val as u64)); 
     }    
 }
 
 #[test]
 fn test_addition_gate() {
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
 
     let seed = [0u8; 32];
     let mut rng = StdRng::from_seed(seed);
@@ -37,66 +42,164 @@ fn test_addition_gate() {
     let x = builder.init();
     let y = builder.init();
 
-    let z = builder.add(x.clone(), y.clone());
+    let z = builder.add(x, y);
 
     for _ in 0..10 {
         let x_val: u16 = rng.gen(); 
         let y_val: u16 = rng.gen();
 
-        builder.set(x.clone(), x_val.into()); 
-        builder.set(y.clone(), y_val.into()); 
+        builder.set(x, F::from(x_val as u64)); 
+        builder.set(y, F::from(y_val as u64)); 
 
-        builder.fill_nodes();
+        builder.fill_nodes().unwrap();
 
-        assert_eq!(z.get(), (x_val as u32 + y_val as u32)); 
+        assert_eq!(builder.get(z), F::from(x_val as u64 + y_val as u64)); 
     }    
 }
 
+#[test]
+fn test_subtraction_and_negation_gates() {
+    let mut builder = Builder::<F>::new();
+
+    let seed = [0u8; 32];
+    let mut rng = StdRng::from_seed(seed);
+
+    let x = builder.init();
+    let y = builder.init();
+
+    let z = builder.sub(x, y);
+    let minus_x = builder.neg(x);
+    let zero = builder.add(x, minus_x);
+
+    assert_eq!(builder.derivation(z), Derivation::Sub);
+    assert_eq!(builder.derivation(minus_x), Derivation::Neg);
+
+    for _ in 0..10 {
+        let x_val: u16 = rng.gen(); 
+        let y_val: u16 = rng.gen();
+
+        builder.set(x, F::from(x_val as u64)); 
+        builder.set(y, F::from(y_val as u64)); 
+
+        builder.fill_nodes().unwrap();
+
+        // differences below zero wrap around the modulus
+        assert_eq!(builder.get(z) + F::from(y_val as u64), F::from(x_val as u64)); 
+        assert_eq!(builder.get(zero), F::zero()); 
+    }    
+
+    builder.set(x, F::from(3));
+    builder.set(y, F::from(5));
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(z), F::from(0xffff_ffff_0000_0001 - 2)); 
+}
+
+#[test]
+fn test_constant_gates() {
+    let mut builder = Builder::<F>::new();
+
+    let x = builder.init();
+    let scaled = builder.mul_const(x, F::from(7));
+    let shifted = builder.add_const(scaled, F::from(5));
+    let wrapped = builder.add_const(x, -F::one());
+
+    assert_eq!(builder.derivation(scaled), Derivation::MulConst);
+    assert_eq!(builder.derivation(shifted), Derivation::AddConst);
+    assert_eq!(builder.parents(shifted), &[scaled.id]);
+    assert_eq!(builder.depth(shifted), 2);
+
+    builder.set(x, F::from(6));
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(scaled), F::from(42));
+    assert_eq!(builder.get(shifted), F::from(47));
+    assert_eq!(builder.get(wrapped), F::from(5));
+
+    builder.set(x, F::zero());
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(wrapped), -F::one());
+}
+
 #[test]
 fn test_builder_set() {
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
 
     let x = builder.init();
-    let y = builder.constant(10);
+    let y = builder.constant(F::from(10));
 
-    let _ = builder.add(x.clone(), y.clone());
+    let _ = builder.add(x, y);
 
     // should fail safely since y is constant node 
-    builder.set(y.clone(), 2); 
-    assert_eq!(y.get(), 10); 
+    builder.set(y, F::from(2)); 
+    assert_eq!(builder.get(y), F::from(10)); 
 
     // should succeed since x is input node 
-    builder.set(x.clone(), 3); 
-    assert_eq!(x.get(), 3); 
+    builder.set(x, F::from(3)); 
+    assert_eq!(builder.get(x), F::from(3)); 
 }
 
 #[test]
 #[should_panic]
 fn test_builder_invalid_set() {
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
 
     let x = builder.init();
-    let y = builder.constant(10);
+    let y = builder.constant(F::from(10));
 
-    let z = builder.add(x.clone(), y.clone());
+    let z = builder.add(x, y);
 
     // trying to set an internal node and accessing it should error
     // since the value has not been computed yet
-    builder.set(z.clone(), 20); 
-    z.get();
+    builder.set(z, F::from(20)); 
+    builder.get(z);
+}
+
+#[test]
+fn test_modular_arithmetic() {
+    let mut builder = Builder::<F>::new();
+
+    let x = builder.init();
+    let y = builder.init();
+
+    let sum = builder.add(x, y);
+    let product = builder.mul(x, y);
+
+    // p - 1 is -1 in the field, so these wrap around the modulus
+    builder.set(x, -F::one());
+    builder.set(y, F::from(2));
+
+    builder.fill_nodes().unwrap();
+
+    assert_eq!(builder.get(sum), F::one());
+    assert_eq!(builder.get(product), -F::from(2));
+    assert_eq!(builder.get(x) * builder.get(x).inverse().unwrap(), F::one());
+    assert_eq!(F::zero().inverse(), None);
+}
+
+#[test]
+fn test_canonical_bytes() {
+    let x = F::from(0x0123_4567_89ab_cdef);
+    let bytes = x.to_canonical_bytes();
+
+    assert_eq!(bytes.len(), F::NUM_BYTES);
+    assert_eq!(F::from_canonical_bytes(&bytes), Some(x));
+    assert_eq!(x.to_u64(), Some(0x0123_4567_89ab_cdef));
+
+    // the modulus itself is not a canonical encoding
+    assert_eq!(F::from_canonical_bytes(&F::MODULUS.to_le_bytes()), None);
+    assert_eq!(F::from_canonical_bytes(&[1, 2, 3]), None);
 }
 
 #[tokio::test]
 async fn test_constraints_satisfied() {
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
 
     let x = builder.init();
     let y = builder.init();
 
-    builder.assert_equal(x.clone(), y.clone());
+    builder.assert_equal(x, y);
 
-    builder.set(x, 10); 
-    builder.set(y, 10); 
+    builder.set(x, F::from(10)); 
+    builder.set(y, F::from(10)); 
 
     let constraint_check = builder.check_constraints().await;
     assert!(constraint_check)
@@ -104,16 +207,215 @@ async fn test_constraints_satisfied() {
 
 #[tokio::test]
 async fn test_constraints_unsatisfied() {
-    let mut builder = Builder::new();
+    let mut builder = Builder::<F>::new();
 
     let x = builder.init();
     let y = builder.init();
 
-    builder.assert_equal(x.clone(), y.clone());
+    builder.assert_equal(x, y);
 
-    builder.set(x, 10); 
-    builder.set(y, 20); 
+    builder.set(x, F::from(10)); 
+    builder.set(y, F::from(20)); 
 
     let constraint_check = builder.check_constraints().await;
     assert!(!constraint_check)
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_try_get() {
+    let mut builder = Builder::<F>::new();
+
+    let x = builder.init();
+    assert_eq!(builder.try_get(x), Err(BuilderError::UnfilledNode { id: x.id }));
+
+    builder.set(x, F::from(7));
+    assert_eq!(builder.try_get(x), Ok(F::from(7)));
+}
+
+#[test]
+fn test_try_get_foreign_node() {
+    let mut builder = Builder::<F>::new();
+    let mut other_builder = Builder::<F>::new();
+
+    let x = builder.init();
+    builder.set(x, F::from(7));
+    let in_range = other_builder.init();
+    let past_end = other_builder.batch_init(3)[2];
+    other_builder.batch_set(&[in_range, past_end], &[F::from(1), F::from(2)]);
+
+    // a handle of another builder is rejected, whether or not its id is in range here
+    assert_eq!(builder.try_get(in_range), Err(BuilderError::ForeignNode { id: in_range.id }));
+    assert_eq!(builder.try_get(past_end), Err(BuilderError::ForeignNode { id: past_end.id }));
+    assert_eq!(other_builder.try_get(x), Err(BuilderError::ForeignNode { id: x.id }));
+}
+
+#[test]
+fn test_try_set() {
+    let mut builder = Builder::<F>::new();
+    let mut other_builder = Builder::<F>::new();
+
+    let x = builder.init();
+    let y = builder.constant(F::from(10));
+    let z = builder.add(x, y);
+    let foreign = other_builder.init();
+
+    assert_eq!(builder.try_set(x, F::from(3)), Ok(()));
+    assert_eq!(builder.try_set(y, F::from(2)), 
+        Err(BuilderError::DerivedNode { id: y.id, derivation: Derivation::Const }));
+    assert_eq!(builder.try_set(z, F::from(2)), 
+        Err(BuilderError::DerivedNode { id: z.id, derivation: Derivation::Add }));
+    assert_eq!(builder.try_set(foreign, F::from(2)), Err(BuilderError::ForeignNode { id: foreign.id }));
+
+    assert_eq!(builder.get(x), F::from(3));
+    assert_eq!(builder.get(y), F::from(10));
+    assert_eq!(builder.try_get(z), Err(BuilderError::UnfilledNode { id: z.id }));
+    assert_eq!(other_builder.try_get(foreign), Err(BuilderError::UnfilledNode { id: foreign.id }));
+}
+
+#[test]
+fn test_try_batch_set() {
+    let mut builder = Builder::<F>::new();
+
+    let inputs = builder.batch_init(3);
+    let constant = builder.constant(F::from(1));
+
+    assert_eq!(builder.try_batch_set(&inputs, &[F::from(1), F::from(2)]), 
+        Err(BuilderError::LengthMismatch { left: 3, right: 2 }));
+
+    // a single invalid node means no node is set
+    let mixed = [inputs[0], constant];
+    assert_eq!(builder.try_batch_set(&mixed, &[F::from(5), F::from(6)]), 
+        Err(BuilderError::DerivedNode { id: constant.id, derivation: Derivation::Const }));
+    assert!(builder.try_get(inputs[0]).is_err());
+
+    assert_eq!(builder.try_batch_set(&inputs, &[F::from(1), F::from(2), F::from(3)]), Ok(()));
+    assert_eq!(builder.get(inputs[2]), F::from(3));
+}
+
+#[test]
+fn test_batch_set_skips_derived_nodes() {
+    let mut builder = Builder::<F>::new();
+    let inputs = builder.batch_init(2);
+    let constant = builder.constant(F::from(1));
+
+    // unlike try_batch_set, the input nodes are set and the constant is left alone
+    builder.batch_set(&[inputs[0], constant, inputs[1]], &[F::from(5), F::from(6), F::from(7)]);
+    assert_eq!(builder.get(inputs[0]), F::from(5));
+    assert_eq!(builder.get(constant), F::from(1));
+    assert_eq!(builder.get(inputs[1]), F::from(7));
+}
+
+#[test]
+#[should_panic(expected = "Length mismatch: 2 nodes on the left, 1 on the right")]
+fn test_batch_set_length_mismatch() {
+    let mut builder = Builder::<F>::new();
+    let inputs = builder.batch_init(2);
+    builder.batch_set(&inputs, &[F::from(1)]);
+}
+
+#[test]
+#[should_panic(expected = "Node with id 3 does not belong to this builder")]
+fn test_gate_foreign_node_past_end() {
+    let mut builder = Builder::<F>::new();
+    let mut other_builder = Builder::<F>::new();
+    let x = builder.init();
+    let foreign = other_builder.batch_init(4)[3];
+    builder.add(x, foreign);
+}
+
+#[test]
+#[should_panic(expected = "Node with id 0 does not belong to this builder")]
+fn test_gate_foreign_node_in_range() {
+    let mut builder = Builder::<F>::new();
+    let mut other_builder = Builder::<F>::new();
+    let x = builder.init();
+    let foreign = other_builder.init();
+    // the id of the foreign node is also an id of this builder, which must not be wired in
+    builder.linear_combination(&[(F::one(), x), (F::from(2), foreign)], F::zero());
+}
+
+#[test]
+fn test_try_hint() {
+    let mut builder = Builder::<F>::new();
+    let mut other_builder = Builder::<F>::new();
+
+    let x = builder.init();
+    let foreign = other_builder.init();
+
+    let empty = builder.try_hint(&[], |_: &[F]| Ok(F::one()));
+    assert_eq!(empty.unwrap_err(), BuilderError::EmptyArguments);
+
+    let mixed = builder.try_hint(&[x, foreign], |val: &[F]| Ok(val[0] + val[1]));
+    assert_eq!(mixed.unwrap_err(), BuilderError::ForeignNode { id: foreign.id });
+
+    let many = builder.try_hint_many(&[], 2, |_: &[F]| Ok(vec![F::one(); 2]));
+    assert_eq!(many.unwrap_err(), BuilderError::EmptyArguments);
+
+    let double = builder.try_hint(std::slice::from_ref(&x), |val: &[F]| Ok(val[0] + val[0])).unwrap();
+    builder.set(x, F::from(4));
+    builder.fill_nodes().unwrap();
+    assert_eq!(builder.get(double), F::from(8));
+}
+
+#[test]
+fn test_try_assert_equal() {
+    let mut builder = Builder::<F>::new();
+    let mut other_builder = Builder::<F>::new();
+
+    let x = builder.batch_init(2);
+    let foreign = other_builder.batch_init(2);
+
+    assert_eq!(builder.try_assert_equal(x[0], foreign[0]), 
+        Err(BuilderError::ForeignNode { id: foreign[0].id }));
+    assert_eq!(builder.try_batch_assert_equal(&x, &foreign[..1]), 
+        Err(BuilderError::LengthMismatch { left: 2, right: 1 }));
+    assert_eq!(builder.try_batch_assert_equal(&x, &foreign), 
+        Err(BuilderError::ForeignNode { id: foreign[0].id }));
+    assert_eq!(builder.try_assert_equal(x[0], x[1]), Ok(()));
+}
+
+#[test]
+fn test_fill_nodes_unset_input() {
+    let mut builder = Builder::<F>::new();
+
+    let x = builder.init();
+    let y = builder.init();
+    let z = builder.mul(x, y);
+    let w = builder.add(z, x);
+
+    builder.set(x, F::from(3));
+
+    assert_eq!(builder.fill_nodes(), Err(EvalError::UnfilledNode { node_id: y.id, output_ids: vec![z.id] }));
+    assert!(builder.try_get(z).is_err());
+    assert!(builder.try_get(w).is_err());
+
+    builder.set(y, F::from(5));
+    assert_eq!(builder.fill_nodes(), Ok(()));
+    assert_eq!(builder.get(w), F::from(18));
+}
+
+#[test]
+fn test_linear_combination() {
+    let mut builder = Builder::<F>::new();
+
+    let inputs = builder.batch_init(8);
+    let terms: Vec<(F, Node)> = inputs.iter().enumerate().map(|(i, &node)| (F::from(i as u64 + 1), node)).collect();
+    let sum = builder.linear_combination(&terms, F::from(100));
+
+    // the whole sum is one gate at the first level
+    assert_eq!(builder.depth(sum), 1);
+    assert_eq!(builder.derivation(sum), Derivation::Linear);
+    assert_eq!(builder.parents(sum), inputs.iter().map(|node| node.id).collect::<Vec<_>>().as_slice());
+
+    // a node may appear in several terms, and coefficients wrap around the modulus
+    let twice_x = builder.linear_combination(&[(F::from(3), inputs[0]), (-F::one(), inputs[0])], F::zero());
+    let constant = builder.linear_combination(&[], F::from(9));
+
+    builder.batch_set(&inputs, &(1..=8).map(F::from).collect::<Vec<_>>());
+    builder.fill_nodes().unwrap();
+
+    // 100 + 1*1 + 2*2 + ... + 8*8
+    assert_eq!(builder.get(sum), F::from(304));
+    assert_eq!(builder.get(twice_x), F::from(2));
+    assert_eq!(builder.get(constant), F::from(9));
+}